@@ -0,0 +1,286 @@
+//! Generates two tables of the same ABI facts about libuiohook: one computed by a C compiler from
+//! `uiohook.h`, and one computed by rustc from `libuiohook-sys`. `tests/abi.rs` compares them.
+//!
+//! The constants are collected from both the header and `sys/src/lib.rs`, so a constant missing on
+//! either side fails to compile rather than going unchecked.
+
+use std::{
+    collections::BTreeSet,
+    env,
+    fmt::Write as _,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Fields of a checked type, as Rust and C names.
+type Fields = &'static [(&'static str, &'static str)];
+
+/// The checked types, which have the same name in Rust and C.
+///
+/// `input_event_data` is anonymous in C, so the generated C code names it through the `data`
+/// member instead.
+const TYPES: &[(&str, Fields)] = &[
+    (
+        "screen_data",
+        &[
+            ("number", "number"),
+            ("x", "x"),
+            ("y", "y"),
+            ("width", "width"),
+            ("height", "height"),
+        ],
+    ),
+    (
+        "keyboard_event_data",
+        &[
+            ("keycode", "keycode"),
+            ("rawcode", "rawcode"),
+            ("keychar", "keychar"),
+        ],
+    ),
+    ("key_pressed_event_data", &[]),
+    ("key_released_event_data", &[]),
+    ("key_typed_event_data", &[]),
+    (
+        "mouse_event_data",
+        &[
+            ("button", "button"),
+            ("clicks", "clicks"),
+            ("x", "x"),
+            ("y", "y"),
+        ],
+    ),
+    ("mouse_pressed_event_data", &[]),
+    ("mouse_released_event_data", &[]),
+    ("mouse_clicked_event_data", &[]),
+    (
+        "mouse_wheel_event_data",
+        &[
+            ("clicks", "clicks"),
+            ("x", "x"),
+            ("y", "y"),
+            ("r#type", "type"),
+            ("amount", "amount"),
+            ("rotation", "rotation"),
+            ("direction", "direction"),
+        ],
+    ),
+    (
+        "input_event_data",
+        &[
+            ("keyboard", "keyboard"),
+            ("mouse", "mouse"),
+            ("wheel", "wheel"),
+        ],
+    ),
+    (
+        "uiohook_event",
+        &[
+            ("r#type", "type"),
+            ("time", "time"),
+            ("mask", "mask"),
+            ("reserved", "reserved"),
+            ("data", "data"),
+        ],
+    ),
+    ("event_type", &[]),
+    ("log_level", &[]),
+];
+
+/// Prefixes of the constants defined by `uiohook.h`.
+const PREFIXES: &[&str] = &[
+    "UIOHOOK_SUCCESS",
+    "UIOHOOK_FAILURE",
+    "UIOHOOK_ERROR_",
+    "LOG_LEVEL_",
+    "EVENT_",
+    "VC_",
+    "CHAR_UNDEFINED",
+    "MASK_",
+    "MOUSE_",
+    "WHEEL_",
+];
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=LIBUIOHOOK_INCLUDE_DIR");
+    println!("cargo:rustc-check-cfg=cfg(abi_header)");
+
+    let manifest_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap());
+    let bindings = manifest_dir.join("../sys/src/lib.rs");
+    println!("cargo:rerun-if-changed={}", bindings.display());
+
+    let out = PathBuf::from(env::var_os("OUT_DIR").unwrap()).join("abi.rs");
+
+    let include_dir = find_include_dir(&manifest_dir);
+
+    let mut constants: BTreeSet<_> =
+        binding_constants(&fs::read_to_string(&bindings).unwrap()).collect();
+
+    let header = include_dir.as_ref().map(|dir| dir.join("uiohook.h"));
+    if let Some(header) = &header {
+        println!("cargo:rerun-if-changed={}", header.display());
+        constants.extend(header_constants(&fs::read_to_string(header).unwrap()));
+    }
+
+    // Each check is a label, the C expression and the Rust expression of the same value.
+    let mut checks = Vec::new();
+
+    for (name, fields) in TYPES {
+        checks.push((
+            format!("size_of({name})"),
+            format!("sizeof({name})"),
+            format!("size_of::<sys::{name}>()"),
+        ));
+        checks.push((
+            format!("align_of({name})"),
+            format!("ALIGNOF({name})"),
+            format!("align_of::<sys::{name}>()"),
+        ));
+
+        for (rust_field, c_field) in *fields {
+            checks.push((
+                format!("offset_of({name}.{c_field})"),
+                format!("offsetof({name}, {c_field})"),
+                format!("offset_of!(sys::{name}, {rust_field})"),
+            ));
+            checks.push((
+                format!("size_of({name}.{c_field})"),
+                format!("sizeof((({name} *) 0)->{c_field})"),
+                format!("field_size(|raw: &sys::{name}| unsafe {{ &raw.{rust_field} }})"),
+            ));
+        }
+    }
+
+    for name in &constants {
+        let rust = if name.starts_with("EVENT_") {
+            format!("sys::event_type::{name}.0")
+        } else if name.starts_with("LOG_LEVEL_") {
+            format!("sys::log_level::{name}.0")
+        } else {
+            format!("sys::{name}")
+        };
+
+        checks.push((name.clone(), name.clone(), rust));
+    }
+
+    let mut rust = String::from(
+        "#[allow(unused_unsafe, clippy::cast_possible_wrap, clippy::cast_lossless)]\n\
+         fn rust_values() -> Vec<(&'static str, i64)> {\n    vec![\n",
+    );
+    for (label, _, expr) in &checks {
+        writeln!(rust, "        ({label:?}, ({expr}) as i64),").unwrap();
+    }
+    rust.push_str("    ]\n}\n\n");
+
+    // Without the header only the Rust side is compiled, which still checks that every listed type
+    // and field exists in the bindings; the test itself is ignored.
+    let (Some(include_dir), Some(header)) = (include_dir, header) else {
+        println!(
+            "cargo:warning=uiohook.h not found, ignoring the ABI test; set \
+             LIBUIOHOOK_INCLUDE_DIR to the directory containing it"
+        );
+        rust.push_str(
+            "const HEADER: &str = \"\";\n\n\
+             fn c_values() -> Vec<i64> {\n    Vec::new()\n}\n",
+        );
+        fs::write(out, rust).unwrap();
+        return;
+    };
+
+    println!("cargo:rustc-cfg=abi_header");
+
+    let mut c = String::from(
+        "#include <stddef.h>\n#include <uiohook.h>\n\n\
+         #define ALIGNOF(type) offsetof(struct { char c; type t; }, t)\n\n\
+         /* Not named by the header. */\n\
+         typedef __typeof__(((uiohook_event *) 0)->data) input_event_data;\n\n\
+         const long long uiohook_abi_values[] = {\n",
+    );
+    for (_, expr, _) in &checks {
+        writeln!(c, "    (long long) ({expr}),").unwrap();
+    }
+    c.push_str("};\n");
+
+    let c_file = out.with_file_name("abi.c");
+    fs::write(&c_file, c).unwrap();
+    cc::Build::new()
+        .file(&c_file)
+        .include(&include_dir)
+        .compile("uiohook_abi");
+
+    write!(
+        rust,
+        "const HEADER: &str = {header:?};\n\n\
+         extern \"C\" {{\n    static uiohook_abi_values: [i64; {len}];\n}}\n\n\
+         fn c_values() -> Vec<i64> {{\n    unsafe {{ uiohook_abi_values }}.to_vec()\n}}\n",
+        len = checks.len(),
+    )
+    .unwrap();
+
+    fs::write(out, rust).unwrap();
+}
+
+/// Looks for `uiohook.h` in `LIBUIOHOOK_INCLUDE_DIR`, the bundled sources, pkg-config and the
+/// usual system directories, in that order.
+fn find_include_dir(manifest_dir: &Path) -> Option<PathBuf> {
+    let mut candidates = Vec::new();
+    candidates.extend(env::var_os("LIBUIOHOOK_INCLUDE_DIR").map(PathBuf::from));
+    candidates.extend(env::var_os("DEP_UIOHOOK_INCLUDE").map(PathBuf::from));
+    candidates.push(manifest_dir.join("../sys/libuiohook/include"));
+
+    if let Ok(library) = pkg_config::Config::new()
+        .cargo_metadata(false)
+        .env_metadata(false)
+        .probe("uiohook")
+    {
+        candidates.extend(library.include_paths);
+    }
+
+    candidates.extend(["/usr/local/include", "/usr/include"].map(PathBuf::from));
+
+    candidates
+        .into_iter()
+        .find(|dir| dir.join("uiohook.h").is_file())
+}
+
+/// Returns the names of the `#define`d and enumerated constants of the header.
+fn header_constants(header: &str) -> impl Iterator<Item = String> + '_ {
+    header.lines().filter_map(|line| {
+        let line = line.trim();
+        let name = match line.strip_prefix("#define") {
+            // Only object-like macros with a value, not include guards or export macros.
+            Some(rest) => {
+                let mut words = rest.split_whitespace();
+                let name = words.next()?;
+                words.next()?;
+                name
+            }
+            None => line
+                .split(|c: char| c == '=' || c == ',' || c.is_whitespace())
+                .next()?,
+        };
+
+        (PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_'))
+        .then(|| name.to_owned())
+    })
+}
+
+/// Returns the names of the constants declared in `sys/src/lib.rs`.
+fn binding_constants(bindings: &str) -> impl Iterator<Item = String> + '_ {
+    bindings.lines().filter_map(|line| {
+        let line = line.trim();
+        let name = match line.strip_prefix("pub const ") {
+            Some(rest) => rest.split([' ', ':']).next()?,
+            // The variants of `c_enum!` declarations.
+            None => line.split_once(" = ")?.0.trim(),
+        };
+
+        PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+            .then(|| name.to_owned())
+    })
+}
@@ -0,0 +1,36 @@
+//! Compares the layout of every `libuiohook-sys` type and the value of every constant against
+//! `uiohook.h`, as computed by `build.rs`.
+
+use std::mem::{align_of, offset_of, size_of};
+
+use libuiohook_sys as sys;
+
+include!(concat!(env!("OUT_DIR"), "/abi.rs"));
+
+/// Returns the size of the field selected by `field`.
+fn field_size<T, F>(_field: fn(&T) -> &F) -> usize {
+    size_of::<F>()
+}
+
+#[test]
+#[cfg_attr(
+    not(abi_header),
+    ignore = "uiohook.h not found; set LIBUIOHOOK_INCLUDE_DIR to the directory containing it"
+)]
+fn matches_header() {
+    let (c_values, rust_values) = (c_values(), rust_values());
+    assert_eq!(c_values.len(), rust_values.len());
+
+    let mismatches: Vec<_> = rust_values
+        .iter()
+        .zip(&c_values)
+        .filter(|((_, rust), c)| rust != *c)
+        .map(|((label, rust), c)| format!("{label}: Rust {rust}, C {c}"))
+        .collect();
+
+    assert!(
+        mismatches.is_empty(),
+        "libuiohook-sys does not match {HEADER}:\n{}",
+        mismatches.join("\n"),
+    );
+}
@@ -0,0 +1,14 @@
+use std::env;
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/logger.c");
+
+    let mut build = cc::Build::new();
+    build.file("src/logger.c").warnings(true);
+
+    if env::var_os("CARGO_FEATURE_V1_3").is_some() {
+        build.define("UIOHOOK_RS_V1_3", None);
+    }
+
+    build.compile("uiohook_rs_logger");
+}
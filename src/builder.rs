@@ -0,0 +1,432 @@
+//! Fluent construction of events, mostly for tests and [`post`](crate::post).
+
+use crate::{
+    event::{
+        Event, HookEvent, KeyboardEvent, MouseButton, MouseEvent, ScrollKind, WheelDirection,
+        WheelEvent,
+    },
+    key::Key,
+    modifiers::Modifiers,
+    sys,
+};
+
+/// Number of units scrolled per wheel click, the usual system default.
+const SCROLL_AMOUNT: u16 = 3;
+
+/// Builds an [`Event`] or a raw [`sys::uiohook_event`] the way libuiohook would emit it.
+///
+/// Each constructor fills in the fields libuiohook sets for that kind of event: keyboard events
+/// carry `CHAR_UNDEFINED` unless they are [`Event::KeyTyped`], button events count a single click,
+/// motion events have no button, and the mask includes the modifier key or button that the event
+/// itself holds down.
+///
+/// ```
+/// use uiohook::{EventBuilder, Key, Modifiers};
+///
+/// let event = EventBuilder::key_pressed(Key::A)
+///     .mask(Modifiers::CTRL_LEFT)
+///     .at(1_000)
+///     .build();
+///
+/// assert!(event.mask().ctrl());
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[must_use]
+pub struct EventBuilder {
+    event: Event,
+    /// Bits of the mask held by the event itself, kept whatever [`EventBuilder::mask`] sets.
+    implied: Modifiers,
+}
+
+impl EventBuilder {
+    /// Starts an [`Event::HookEnabled`].
+    pub fn hook_enabled() -> Self {
+        Self::new(Event::HookEnabled(HookEvent::default()), Modifiers::empty())
+    }
+
+    /// Starts an [`Event::HookDisabled`].
+    pub fn hook_disabled() -> Self {
+        Self::new(
+            Event::HookDisabled(HookEvent::default()),
+            Modifiers::empty(),
+        )
+    }
+
+    /// Starts an [`Event::KeyPressed`]; pressing a modifier key includes it in the mask.
+    pub fn key_pressed(key: Key) -> Self {
+        Self::new(Event::KeyPressed(keyboard(key)), Modifiers::of_key(key))
+    }
+
+    /// Starts an [`Event::KeyReleased`].
+    pub fn key_released(key: Key) -> Self {
+        Self::new(Event::KeyReleased(keyboard(key)), Modifiers::empty())
+    }
+
+    /// Starts an [`Event::KeyTyped`] producing `ch`, with the key set to [`Key::Undefined`].
+    ///
+    /// libuiohook delivers characters outside the Basic Multilingual Plane as two events, one per
+    /// UTF-16 code unit; this builds the first one, use [`EventBuilder::keychar`] for the second.
+    pub fn key_typed(ch: char) -> Self {
+        let mut units = [0; 2];
+        let keychar = ch.encode_utf16(&mut units)[0];
+
+        Self::new(
+            Event::KeyTyped(KeyboardEvent {
+                keychar,
+                ..keyboard(Key::Undefined)
+            }),
+            Modifiers::empty(),
+        )
+    }
+
+    /// Starts an [`Event::MousePressed`] of a single click; the button is included in the mask.
+    pub fn mouse_pressed(button: MouseButton) -> Self {
+        Self::new(
+            Event::MousePressed(mouse(Some(button), 1)),
+            Modifiers::of_button(button),
+        )
+    }
+
+    /// Starts an [`Event::MouseReleased`] of a single click.
+    pub fn mouse_released(button: MouseButton) -> Self {
+        Self::new(
+            Event::MouseReleased(mouse(Some(button), 1)),
+            Modifiers::empty(),
+        )
+    }
+
+    /// Starts an [`Event::MouseClicked`] of a single click.
+    pub fn mouse_clicked(button: MouseButton) -> Self {
+        Self::new(
+            Event::MouseClicked(mouse(Some(button), 1)),
+            Modifiers::empty(),
+        )
+    }
+
+    /// Starts an [`Event::MouseMoved`] to the given position.
+    pub fn mouse_moved(x: i16, y: i16) -> Self {
+        Self::new(Event::MouseMoved(mouse(None, 0)), Modifiers::empty()).position(x, y)
+    }
+
+    /// Starts an [`Event::MouseDragged`] to the given position while `button` is held, which is
+    /// included in the mask.
+    pub fn mouse_dragged(button: MouseButton, x: i16, y: i16) -> Self {
+        Self::new(
+            Event::MouseDragged(mouse(None, 0)),
+            Modifiers::of_button(button),
+        )
+        .position(x, y)
+    }
+
+    /// Starts an [`Event::Wheel`] rotating the wheel by `rotation` clicks along `direction`;
+    /// negative values scroll up or left.
+    ///
+    /// Scrolls three units per click, as most systems do by default.
+    pub fn wheel(direction: WheelDirection, rotation: i16) -> Self {
+        Self::new(
+            Event::Wheel(WheelEvent {
+                time: 0,
+                mask: Modifiers::empty(),
+                clicks: 1,
+                x: 0,
+                y: 0,
+                kind: ScrollKind::Unit,
+                amount: SCROLL_AMOUNT,
+                rotation,
+                direction,
+            }),
+            Modifiers::empty(),
+        )
+    }
+
+    fn new(event: Event, implied: Modifiers) -> Self {
+        Self { event, implied }
+    }
+
+    /// Sets the modifiers held during the event.
+    ///
+    /// The bits implied by the event itself, such as the button being pressed, are always kept.
+    pub fn mask(mut self, mask: Modifiers) -> Self {
+        let mask = mask | self.implied;
+        match &mut self.event {
+            Event::HookEnabled(event) | Event::HookDisabled(event) => event.mask = mask,
+            Event::KeyPressed(event) | Event::KeyReleased(event) | Event::KeyTyped(event) => {
+                event.mask = mask;
+            }
+            Event::MousePressed(event)
+            | Event::MouseReleased(event)
+            | Event::MouseClicked(event)
+            | Event::MouseMoved(event)
+            | Event::MouseDragged(event) => event.mask = mask,
+            Event::Wheel(event) => event.mask = mask,
+        }
+        self
+    }
+
+    /// Sets the time of the event, in milliseconds.
+    pub fn at(mut self, time: u64) -> Self {
+        match &mut self.event {
+            Event::HookEnabled(event) | Event::HookDisabled(event) => event.time = time,
+            Event::KeyPressed(event) | Event::KeyReleased(event) | Event::KeyTyped(event) => {
+                event.time = time;
+            }
+            Event::MousePressed(event)
+            | Event::MouseReleased(event)
+            | Event::MouseClicked(event)
+            | Event::MouseMoved(event)
+            | Event::MouseDragged(event) => event.time = time,
+            Event::Wheel(event) => event.time = time,
+        }
+        self
+    }
+
+    /// Sets the cursor position of a mouse or wheel event; ignored by other events.
+    pub fn position(mut self, x: i16, y: i16) -> Self {
+        match &mut self.event {
+            Event::MousePressed(event)
+            | Event::MouseReleased(event)
+            | Event::MouseClicked(event)
+            | Event::MouseMoved(event)
+            | Event::MouseDragged(event) => (event.x, event.y) = (x, y),
+            Event::Wheel(event) => (event.x, event.y) = (x, y),
+            _ => {}
+        }
+        self
+    }
+
+    /// Sets the click count of a mouse or wheel event; ignored by other events.
+    pub fn clicks(mut self, clicks: u16) -> Self {
+        match &mut self.event {
+            Event::MousePressed(event)
+            | Event::MouseReleased(event)
+            | Event::MouseClicked(event)
+            | Event::MouseMoved(event)
+            | Event::MouseDragged(event) => event.clicks = clicks,
+            Event::Wheel(event) => event.clicks = clicks,
+            _ => {}
+        }
+        self
+    }
+
+    /// Sets the platform specific key code of a keyboard event; ignored by other events.
+    pub fn rawcode(mut self, rawcode: u16) -> Self {
+        if let Event::KeyPressed(event) | Event::KeyReleased(event) | Event::KeyTyped(event) =
+            &mut self.event
+        {
+            event.rawcode = rawcode;
+        }
+        self
+    }
+
+    /// Sets the UTF-16 code unit of a keyboard event; ignored by other events.
+    pub fn keychar(mut self, keychar: u16) -> Self {
+        if let Event::KeyPressed(event) | Event::KeyReleased(event) | Event::KeyTyped(event) =
+            &mut self.event
+        {
+            event.keychar = keychar;
+        }
+        self
+    }
+
+    /// Sets the scroll type and amount per click of a wheel event; ignored by other events.
+    pub fn amount(mut self, kind: ScrollKind, amount: u16) -> Self {
+        if let Event::Wheel(event) = &mut self.event {
+            (event.kind, event.amount) = (kind, amount);
+        }
+        self
+    }
+
+    /// Returns the event.
+    #[must_use]
+    pub fn build(self) -> Event {
+        // Applies the implied bits even if `mask` was never called.
+        self.mask(self.event.mask()).event
+    }
+
+    /// Returns the event as a raw `uiohook_event`, with the data variant matching its type.
+    #[must_use]
+    pub fn build_raw(self) -> sys::uiohook_event {
+        self.build().into()
+    }
+}
+
+impl From<EventBuilder> for Event {
+    fn from(builder: EventBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<EventBuilder> for sys::uiohook_event {
+    fn from(builder: EventBuilder) -> Self {
+        builder.build_raw()
+    }
+}
+
+fn keyboard(key: Key) -> KeyboardEvent {
+    KeyboardEvent {
+        time: 0,
+        mask: Modifiers::empty(),
+        key,
+        rawcode: 0,
+        keychar: sys::CHAR_UNDEFINED,
+    }
+}
+
+fn mouse(button: Option<MouseButton>, clicks: u16) -> MouseEvent {
+    MouseEvent {
+        time: 0,
+        mask: Modifiers::empty(),
+        button,
+        clicks,
+        x: 0,
+        y: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn implies_the_held_key_or_button() {
+        let masks = [
+            (
+                EventBuilder::key_pressed(Key::ShiftLeft),
+                Modifiers::SHIFT_LEFT,
+            ),
+            (EventBuilder::key_pressed(Key::A), Modifiers::empty()),
+            (
+                EventBuilder::key_released(Key::ShiftLeft),
+                Modifiers::empty(),
+            ),
+            (
+                EventBuilder::mouse_pressed(MouseButton::Right),
+                Modifiers::BUTTON2,
+            ),
+            (
+                EventBuilder::mouse_pressed(MouseButton::Other(6)),
+                Modifiers::empty(),
+            ),
+            (
+                EventBuilder::mouse_released(MouseButton::Right),
+                Modifiers::empty(),
+            ),
+            (
+                EventBuilder::mouse_dragged(MouseButton::Left, 1, 2),
+                Modifiers::BUTTON1,
+            ),
+            (EventBuilder::mouse_moved(1, 2), Modifiers::empty()),
+        ];
+
+        for (builder, implied) in masks {
+            assert_eq!(builder.build().mask(), implied, "{builder:?}");
+            assert_eq!(
+                builder.mask(Modifiers::empty()).build().mask(),
+                implied,
+                "{builder:?}"
+            );
+            assert_eq!(
+                builder.mask(Modifiers::CTRL_RIGHT).build().mask(),
+                implied | Modifiers::CTRL_RIGHT,
+                "{builder:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn fills_in_the_defaults() {
+        let Event::MousePressed(pressed) = EventBuilder::mouse_pressed(MouseButton::Left).build()
+        else {
+            unreachable!();
+        };
+        assert_eq!(
+            (pressed.button, pressed.clicks),
+            (Some(MouseButton::Left), 1)
+        );
+
+        let Event::MouseMoved(moved) = EventBuilder::mouse_moved(3, 4).build() else {
+            unreachable!();
+        };
+        assert_eq!(
+            (moved.button, moved.clicks, moved.x, moved.y),
+            (None, 0, 3, 4)
+        );
+
+        let Event::Wheel(wheel) = EventBuilder::wheel(WheelDirection::Vertical, -1).build() else {
+            unreachable!();
+        };
+        assert_eq!(
+            (wheel.kind, wheel.amount, wheel.clicks),
+            (ScrollKind::Unit, 3, 1)
+        );
+
+        let Event::KeyPressed(key) = EventBuilder::key_pressed(Key::A).build() else {
+            unreachable!();
+        };
+        assert_eq!(key.keychar, sys::CHAR_UNDEFINED);
+        assert_eq!(key.char(), None);
+    }
+
+    #[test]
+    fn ignores_fields_of_other_kinds() {
+        let builders = [
+            EventBuilder::hook_enabled(),
+            EventBuilder::key_pressed(Key::A),
+            EventBuilder::key_typed('a'),
+        ];
+        for builder in builders {
+            assert_eq!(
+                builder
+                    .position(1, 2)
+                    .clicks(3)
+                    .amount(ScrollKind::Block, 1),
+                builder,
+                "{builder:?}"
+            );
+        }
+
+        let builders = [
+            EventBuilder::hook_disabled(),
+            EventBuilder::mouse_pressed(MouseButton::Left),
+            EventBuilder::wheel(WheelDirection::Horizontal, 1),
+        ];
+        for builder in builders {
+            assert_eq!(builder.rawcode(1).keychar(2), builder, "{builder:?}");
+        }
+    }
+
+    #[test]
+    fn splits_characters_outside_the_bmp() {
+        let Event::KeyTyped(typed) = EventBuilder::key_typed('a').build() else {
+            unreachable!();
+        };
+        assert_eq!((typed.key, typed.char()), (Key::Undefined, Some('a')));
+
+        // U+1F600 is the surrogate pair D83D DE00.
+        let Event::KeyTyped(high) = EventBuilder::key_typed('😀').build() else {
+            unreachable!();
+        };
+        assert_eq!((high.keychar, high.char()), (0xd83d, None));
+
+        let Event::KeyTyped(low) = EventBuilder::key_typed('😀').keychar(0xde00).build() else {
+            unreachable!();
+        };
+        assert_eq!(
+            char::decode_utf16([high.keychar, low.keychar]).collect::<Result<String, _>>(),
+            Ok("😀".to_owned())
+        );
+    }
+
+    #[test]
+    fn builds_raw_events() {
+        let builder = EventBuilder::mouse_clicked(MouseButton::Middle)
+            .position(5, 6)
+            .at(7);
+
+        assert_eq!(
+            builder.build_raw(),
+            sys::uiohook_event::from(builder.build())
+        );
+        assert_eq!(Event::from(builder), builder.build());
+    }
+}
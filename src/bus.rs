@@ -0,0 +1,400 @@
+//! Fan-out of the events of a single hook to several subscribers.
+
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard, PoisonError, Weak,
+    },
+};
+
+use crate::{
+    event::{Event, EventKind},
+    hook::HookBuilder,
+};
+
+type Handler = Box<dyn FnMut(&Event) -> Propagation + Send>;
+
+/// Whether an event should be passed on to lower priority subscribers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Propagation {
+    /// Pass the event on.
+    #[default]
+    Continue,
+    /// Hide the event from every subscriber with a lower priority.
+    Stop,
+}
+
+/// Delivers every event to its subscribers, highest priority first.
+///
+/// libuiohook supports a single dispatcher per process; a bus lets independent parts of a program
+/// share it. Register the bus with [`HookBuilder::on_bus`], or feed it with
+/// [`EventBus::dispatch`]. Clones share the same subscribers.
+///
+/// A panicking subscriber is handled by the [`PanicPolicy`](crate::PanicPolicy) of the hook, which
+/// applies to the bus as a whole: `DropHandler` stops the delivery to every subscriber, while
+/// `Continue` keeps calling all of them, including the one that panicked.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Arc<Subscriber>>>>,
+}
+
+impl EventBus {
+    /// Creates a bus without subscribers.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Subscribes `handler` to every event, with priority 0.
+    pub fn subscribe<F>(&self, handler: F) -> Subscription
+    where
+        F: FnMut(&Event) -> Propagation + Send + 'static,
+    {
+        self.subscriber().subscribe(handler)
+    }
+
+    /// Creates a builder to configure a subscription.
+    pub fn subscriber(&self) -> SubscriberBuilder<'_> {
+        SubscriberBuilder {
+            bus: self,
+            priority: 0,
+            kinds: None,
+        }
+    }
+
+    /// Delivers `event` to the subscribers, highest priority first, until one of them returns
+    /// [`Propagation::Stop`].
+    ///
+    /// Subscribers with the same priority are called in the order they subscribed. Handlers may
+    /// subscribe and unsubscribe, including themselves, without deadlocking.
+    pub fn dispatch(&self, event: &Event) -> Propagation {
+        // The list is copied so that handlers can modify the bus while the event is delivered.
+        let subscribers = self.lock().clone();
+        let kind = event.kind();
+
+        for subscriber in subscribers {
+            if !subscriber.accepts(kind) {
+                continue;
+            }
+
+            let mut handler = subscriber
+                .handler
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+
+            // Checked again as the copy may include subscribers removed since.
+            if subscriber.active.load(Ordering::Acquire) && handler(event) == Propagation::Stop {
+                return Propagation::Stop;
+            }
+        }
+
+        Propagation::Continue
+    }
+
+    /// Returns the number of active subscriptions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns `true` if there are no active subscriptions.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Vec<Arc<Subscriber>>> {
+        self.subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for EventBus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventBus")
+            .field("subscribers", &self.len())
+            .finish()
+    }
+}
+
+impl HookBuilder {
+    /// Delivers every event produced by the hook to the subscribers of `bus`.
+    ///
+    /// Replaces any dispatcher set on the builder.
+    pub fn on_bus(self, bus: &EventBus) -> Self {
+        let bus = bus.clone();
+        self.on_event(move |event| {
+            bus.dispatch(&event);
+        })
+    }
+}
+
+/// A registered handler.
+struct Subscriber {
+    priority: i32,
+    kinds: Option<Vec<EventKind>>,
+    handler: Mutex<Handler>,
+    /// Cleared by [`Subscription::unsubscribe`], for dispatches that copied the list earlier.
+    active: AtomicBool,
+}
+
+impl Subscriber {
+    fn accepts(&self, kind: EventKind) -> bool {
+        self.kinds
+            .as_ref()
+            .map_or(true, |kinds| kinds.contains(&kind))
+    }
+}
+
+/// Configures a subscription before registering it with [`SubscriberBuilder::subscribe`].
+#[derive(Debug)]
+#[must_use]
+pub struct SubscriberBuilder<'a> {
+    bus: &'a EventBus,
+    priority: i32,
+    kinds: Option<Vec<EventKind>>,
+}
+
+impl SubscriberBuilder<'_> {
+    /// Sets the priority of the subscriber; higher priorities see events first.
+    ///
+    /// Defaults to 0.
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Only delivers events of the given kinds.
+    ///
+    /// Defaults to every kind. Can be called several times to add more kinds.
+    pub fn kinds(mut self, kinds: impl IntoIterator<Item = EventKind>) -> Self {
+        self.kinds.get_or_insert_with(Vec::new).extend(kinds);
+        self
+    }
+
+    /// Registers `handler` with the bus.
+    ///
+    /// The handler is called from the hook thread, so it should return quickly.
+    pub fn subscribe<F>(self, handler: F) -> Subscription
+    where
+        F: FnMut(&Event) -> Propagation + Send + 'static,
+    {
+        let subscriber = Arc::new(Subscriber {
+            priority: self.priority,
+            kinds: self.kinds,
+            handler: Mutex::new(Box::new(handler)),
+            active: AtomicBool::new(true),
+        });
+
+        let mut subscribers = self.bus.lock();
+        let index = subscribers.partition_point(|other| other.priority >= subscriber.priority);
+        subscribers.insert(index, Arc::clone(&subscriber));
+
+        Subscription {
+            bus: Arc::downgrade(&self.bus.subscribers),
+            subscriber: Some(subscriber),
+        }
+    }
+}
+
+/// A handle to a subscriber of an [`EventBus`].
+///
+/// Dropping the handle unsubscribes; use [`Subscription::detach`] to keep the subscriber for the
+/// lifetime of the bus instead.
+#[must_use = "dropping a subscription unsubscribes it"]
+pub struct Subscription {
+    bus: Weak<Mutex<Vec<Arc<Subscriber>>>>,
+    subscriber: Option<Arc<Subscriber>>,
+}
+
+impl Subscription {
+    /// Returns the priority of the subscriber.
+    #[must_use]
+    pub fn priority(&self) -> i32 {
+        self.subscriber
+            .as_ref()
+            .map_or(0, |subscriber| subscriber.priority)
+    }
+
+    /// Removes the subscriber from the bus.
+    ///
+    /// The handler is not called for any event delivered after this returns, though a call that
+    /// is already running finishes normally.
+    pub fn unsubscribe(mut self) {
+        self.remove();
+    }
+
+    /// Keeps the subscriber registered until the bus is dropped.
+    pub fn detach(mut self) {
+        self.subscriber = None;
+    }
+
+    fn remove(&mut self) {
+        let Some(subscriber) = self.subscriber.take() else {
+            return;
+        };
+
+        subscriber.active.store(false, Ordering::Release);
+
+        if let Some(bus) = self.bus.upgrade() {
+            bus.lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .retain(|other| !Arc::ptr_eq(other, &subscriber));
+        }
+    }
+}
+
+impl fmt::Debug for Subscription {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Subscription")
+            .field("priority", &self.priority())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{builder::EventBuilder, event::MouseButton, key::Key};
+
+    type Log = Arc<Mutex<Vec<&'static str>>>;
+
+    /// Returns a handler that logs `name` and returns `propagation`.
+    fn record(
+        log: &Log,
+        name: &'static str,
+        propagation: Propagation,
+    ) -> impl FnMut(&Event) -> Propagation + Send + 'static {
+        let log = Arc::clone(log);
+        move |_| {
+            log.lock().unwrap().push(name);
+            propagation
+        }
+    }
+
+    fn take(log: &Log) -> Vec<&'static str> {
+        std::mem::take(&mut log.lock().unwrap())
+    }
+
+    fn key_pressed() -> Event {
+        EventBuilder::key_pressed(Key::A).build()
+    }
+
+    #[test]
+    fn calls_higher_priorities_first() {
+        let (bus, log) = (EventBus::new(), Log::default());
+        let subscribe = |name, priority| {
+            bus.subscriber()
+                .priority(priority)
+                .subscribe(record(&log, name, Propagation::Continue))
+        };
+
+        let _subscriptions = [
+            subscribe("a", 0),
+            subscribe("b", 10),
+            subscribe("c", 0),
+            subscribe("d", -5),
+            subscribe("e", 10),
+        ];
+
+        assert_eq!(bus.dispatch(&key_pressed()), Propagation::Continue);
+        assert_eq!(take(&log), ["b", "e", "a", "c", "d"]);
+    }
+
+    #[test]
+    fn filters_kinds() {
+        let (bus, log) = (EventBus::new(), Log::default());
+        let _keys = bus
+            .subscriber()
+            .kinds([EventKind::KeyPressed])
+            .kinds([EventKind::KeyReleased])
+            .subscribe(record(&log, "keys", Propagation::Continue));
+        let _all = bus.subscribe(record(&log, "all", Propagation::Continue));
+
+        bus.dispatch(&key_pressed());
+        bus.dispatch(&EventBuilder::key_released(Key::A).build());
+        bus.dispatch(&EventBuilder::mouse_pressed(MouseButton::Left).build());
+
+        assert_eq!(take(&log), ["keys", "all", "keys", "all", "all"]);
+    }
+
+    #[test]
+    fn stops_propagation() {
+        let (bus, log) = (EventBus::new(), Log::default());
+        let _high =
+            bus.subscriber()
+                .priority(1)
+                .subscribe(record(&log, "high", Propagation::Continue));
+        let _stop = bus.subscribe(record(&log, "stop", Propagation::Stop));
+        let _same = bus.subscribe(record(&log, "same", Propagation::Continue));
+        let _low =
+            bus.subscriber()
+                .priority(-1)
+                .subscribe(record(&log, "low", Propagation::Continue));
+
+        assert_eq!(bus.dispatch(&key_pressed()), Propagation::Stop);
+        assert_eq!(take(&log), ["high", "stop"]);
+    }
+
+    #[test]
+    fn unsubscribes() {
+        let (bus, log) = (EventBus::new(), Log::default());
+        let dropped = bus.subscribe(record(&log, "dropped", Propagation::Continue));
+        let unsubscribed = bus.subscribe(record(&log, "unsubscribed", Propagation::Continue));
+        let _kept = bus.subscribe(record(&log, "kept", Propagation::Continue));
+        assert_eq!(bus.len(), 3);
+
+        drop(dropped);
+        unsubscribed.unsubscribe();
+
+        assert_eq!(bus.len(), 1);
+        bus.dispatch(&key_pressed());
+        assert_eq!(take(&log), ["kept"]);
+    }
+
+    #[test]
+    fn unsubscribes_from_handlers() {
+        let (bus, log) = (EventBus::new(), Log::default());
+        let subscriptions: Arc<Mutex<Vec<Subscription>>> = Arc::default();
+
+        // Removes itself and the lower priority subscriber on its first call.
+        let first = bus.subscriber().priority(1).subscribe({
+            let (log, subscriptions) = (Arc::clone(&log), Arc::clone(&subscriptions));
+            move |_| {
+                log.lock().unwrap().push("first");
+                for subscription in subscriptions.lock().unwrap().drain(..) {
+                    subscription.unsubscribe();
+                }
+                Propagation::Continue
+            }
+        });
+        let second = bus.subscribe(record(&log, "second", Propagation::Continue));
+        subscriptions.lock().unwrap().extend([first, second]);
+
+        bus.dispatch(&key_pressed());
+        bus.dispatch(&key_pressed());
+
+        assert_eq!(take(&log), ["first"]);
+        assert!(bus.is_empty());
+    }
+
+    #[test]
+    fn keeps_detached_subscribers() {
+        let (bus, log) = (EventBus::new(), Log::default());
+        bus.subscribe(record(&log, "detached", Propagation::Continue))
+            .detach();
+
+        bus.dispatch(&key_pressed());
+
+        assert_eq!(bus.len(), 1);
+        assert_eq!(take(&log), ["detached"]);
+    }
+}
@@ -0,0 +1,192 @@
+//! Delivery of events through a bounded channel, for consumers that pull events in a loop.
+
+use std::{
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, RecvError, RecvTimeoutError, TryRecvError, TrySendError},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::{
+    error::Result,
+    event::Event,
+    hook::{Hook, HookBuilder, HookHandle},
+};
+
+/// Number of events [`Hook::spawn_channel`] buffers before dropping new ones.
+pub const DEFAULT_CHANNEL_CAPACITY: usize = 1024;
+
+impl Hook {
+    /// Runs a hook that sends its events to the returned receiver.
+    ///
+    /// Up to [`DEFAULT_CHANNEL_CAPACITY`] events are buffered; use
+    /// [`HookBuilder::spawn_channel`] to configure the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`HookBuilder::spawn`].
+    pub fn spawn_channel() -> Result<(EventReceiver, HookHandle)> {
+        Self::builder().spawn_channel(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
+impl HookBuilder {
+    /// Runs the hook on a new thread, sending its events to the returned receiver.
+    ///
+    /// At most `capacity` events are buffered, and at least one: a `capacity` of 0 is treated as 1,
+    /// since the hook thread never waits for the receiver and could not hand over any event.
+    /// Events arriving while the buffer is full are dropped and counted by
+    /// [`EventReceiver::dropped`]. Replaces any dispatcher set on the builder.
+    ///
+    /// The receiver disconnects once the hook has stopped and every buffered event was received.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`HookBuilder::spawn`].
+    pub fn spawn_channel(self, capacity: usize) -> Result<(EventReceiver, HookHandle)> {
+        let (sender, receiver) = mpsc::sync_channel(capacity.max(1));
+        let dropped = Arc::new(AtomicU64::new(0));
+
+        let handle = self
+            .on_event({
+                let dropped = Arc::clone(&dropped);
+                move |event| {
+                    if let Err(TrySendError::Full(_)) = sender.try_send(event) {
+                        dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            })
+            .spawn()?;
+
+        Ok((EventReceiver { receiver, dropped }, handle))
+    }
+}
+
+/// The receiving half of [`Hook::spawn_channel`].
+#[derive(Debug)]
+pub struct EventReceiver {
+    receiver: mpsc::Receiver<Event>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl EventReceiver {
+    /// Waits for the next event.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error once the hook has stopped and every buffered event was received.
+    pub fn recv(&self) -> Result<Event, RecvError> {
+        self.receiver.recv()
+    }
+
+    /// Returns the next event if one is buffered, without waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TryRecvError::Empty`] if no event is buffered, or
+    /// [`TryRecvError::Disconnected`] once the hook has stopped and every buffered event was
+    /// received.
+    pub fn try_recv(&self) -> Result<Event, TryRecvError> {
+        self.receiver.try_recv()
+    }
+
+    /// Waits up to `timeout` for the next event.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvTimeoutError::Timeout`] if no event arrived in time, or
+    /// [`RecvTimeoutError::Disconnected`] once the hook has stopped and every buffered event was
+    /// received.
+    pub fn recv_timeout(&self, timeout: Duration) -> Result<Event, RecvTimeoutError> {
+        self.receiver.recv_timeout(timeout)
+    }
+
+    /// Returns an iterator that waits for events until the hook has stopped.
+    #[must_use]
+    pub fn iter(&self) -> mpsc::Iter<'_, Event> {
+        self.receiver.iter()
+    }
+
+    /// Returns an iterator over the buffered events, without waiting.
+    #[must_use]
+    pub fn try_iter(&self) -> mpsc::TryIter<'_, Event> {
+        self.receiver.try_iter()
+    }
+
+    /// Returns the number of events dropped so far because the buffer was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl<'a> IntoIterator for &'a EventReceiver {
+    type Item = Event;
+    type IntoIter = mpsc::Iter<'a, Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for EventReceiver {
+    type Item = Event;
+    type IntoIter = mpsc::IntoIter<Event>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.receiver.into_iter()
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::{builder::EventBuilder, key::Key, sys::mock};
+
+    #[test]
+    fn receives_events_until_stopped() {
+        let _mock = mock::lock();
+        mock::set_stop_when_idle(true);
+        mock::script([EventBuilder::key_pressed(Key::A).build_raw()]);
+
+        let (events, handle) = Hook::spawn_channel().unwrap();
+
+        assert_eq!(
+            events.iter().collect::<Vec<_>>(),
+            [
+                EventBuilder::hook_enabled().build(),
+                EventBuilder::key_pressed(Key::A).build(),
+                EventBuilder::hook_disabled().build(),
+            ]
+        );
+        assert_eq!(events.dropped(), 0);
+        handle.stop().unwrap();
+    }
+
+    #[test]
+    fn drops_events_when_full() {
+        let _mock = mock::lock();
+        mock::set_stop_when_idle(true);
+        mock::script(
+            [Key::A, Key::B, Key::C].map(|key| EventBuilder::key_pressed(key).build_raw()),
+        );
+
+        // A capacity of 0 still buffers one event.
+        let (events, handle) = Hook::builder().spawn_channel(0).unwrap();
+        // Waits for the last event, which is dispatched after the hook reports being disabled.
+        while !handle.is_finished() {
+            thread::yield_now();
+        }
+
+        assert_eq!(
+            events.try_iter().collect::<Vec<_>>(),
+            [EventBuilder::hook_enabled().build()]
+        );
+        assert_eq!(events.dropped(), 4);
+        handle.stop().unwrap();
+    }
+}
@@ -0,0 +1,390 @@
+use std::{error, fmt, io, os::raw::c_int};
+
+use crate::{event::EventKind, sys};
+
+/// A specialized [`Result`](std::result::Result) type for uiohook operations.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// The error type for uiohook operations.
+///
+/// Status codes returned by libuiohook are mapped to their own variants, grouped by the platform
+/// that can produce them.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// Another hook is already active in this process.
+    AlreadyRunning,
+    /// The thread running the hook could not be spawned.
+    Spawn(io::Error),
+    /// The hook did not reach the awaited state in time.
+    Timeout,
+    /// libuiohook cannot post events of this kind.
+    Unpostable(EventKind),
+    /// libuiohook could not be loaded at runtime.
+    #[cfg(feature = "dynamic")]
+    Load(sys::LoadError),
+    /// `UIOHOOK_FAILURE`.
+    Failure,
+    /// `UIOHOOK_ERROR_OUT_OF_MEMORY`.
+    OutOfMemory,
+    /// One of the `UIOHOOK_ERROR_X_*` codes.
+    X11(X11Error),
+    /// One of the Windows specific codes.
+    Windows(WindowsError),
+    /// One of the Darwin specific codes.
+    Darwin(DarwinError),
+    /// A status code this crate does not know about.
+    Unknown(i32),
+}
+
+impl Error {
+    /// Maps a libuiohook status code to an error, returning `None` for `UIOHOOK_SUCCESS`.
+    #[must_use]
+    pub fn from_code(code: c_int) -> Option<Self> {
+        Some(match code {
+            sys::UIOHOOK_SUCCESS => return None,
+            sys::UIOHOOK_FAILURE => Self::Failure,
+            sys::UIOHOOK_ERROR_OUT_OF_MEMORY => Self::OutOfMemory,
+
+            sys::UIOHOOK_ERROR_X_OPEN_DISPLAY => Self::X11(X11Error::OpenDisplay),
+            sys::UIOHOOK_ERROR_X_RECORD_NOT_FOUND => Self::X11(X11Error::RecordNotFound),
+            sys::UIOHOOK_ERROR_X_RECORD_ALLOC_RANGE => Self::X11(X11Error::RecordAllocRange),
+            sys::UIOHOOK_ERROR_X_RECORD_CREATE_CONTEXT => Self::X11(X11Error::RecordCreateContext),
+            sys::UIOHOOK_ERROR_X_RECORD_ENABLE_CONTEXT => Self::X11(X11Error::RecordEnableContext),
+            sys::UIOHOOK_ERROR_X_RECORD_GET_CONTEXT => Self::X11(X11Error::RecordGetContext),
+
+            sys::UIOHOOK_ERROR_SET_WINDOWS_HOOK_EX => Self::Windows(WindowsError::SetWindowsHookEx),
+            sys::UIOHOOK_ERROR_GET_MODULE_HANDLE => Self::Windows(WindowsError::GetModuleHandle),
+
+            sys::UIOHOOK_ERROR_AXAPI_DISABLED => Self::Darwin(DarwinError::AxApiDisabled),
+            sys::UIOHOOK_ERROR_CREATE_EVENT_PORT => Self::Darwin(DarwinError::CreateEventPort),
+            sys::UIOHOOK_ERROR_CREATE_RUN_LOOP_SOURCE => {
+                Self::Darwin(DarwinError::CreateRunLoopSource)
+            }
+            sys::UIOHOOK_ERROR_GET_RUNLOOP => Self::Darwin(DarwinError::GetRunLoop),
+            sys::UIOHOOK_ERROR_CREATE_OBSERVER => Self::Darwin(DarwinError::CreateObserver),
+
+            code => Self::Unknown(code),
+        })
+    }
+
+    /// Returns the libuiohook status code of the error, if it was produced by libuiohook.
+    #[must_use]
+    pub fn code(&self) -> Option<c_int> {
+        Some(match self {
+            Self::AlreadyRunning | Self::Spawn(_) | Self::Timeout | Self::Unpostable(_) => {
+                return None
+            }
+            #[cfg(feature = "dynamic")]
+            Self::Load(_) => return None,
+            Self::Failure => sys::UIOHOOK_FAILURE,
+            Self::OutOfMemory => sys::UIOHOOK_ERROR_OUT_OF_MEMORY,
+            Self::X11(err) => err.code(),
+            Self::Windows(err) => err.code(),
+            Self::Darwin(err) => err.code(),
+            Self::Unknown(code) => *code,
+        })
+    }
+
+    /// Returns the name of the platform subsystem that failed, if known.
+    #[must_use]
+    pub fn subsystem(&self) -> Option<&'static str> {
+        match self {
+            Self::X11(err) => Some(err.subsystem()),
+            Self::Windows(err) => Some(err.subsystem()),
+            Self::Darwin(err) => Some(err.subsystem()),
+            _ => None,
+        }
+    }
+
+    /// Makes sure libuiohook is available before calling into it.
+    ///
+    /// Only the `dynamic` feature can fail here; otherwise the library was linked at build time,
+    /// or replaced by the `mock` feature.
+    #[cfg_attr(
+        any(not(feature = "dynamic"), feature = "mock"),
+        allow(clippy::unnecessary_wraps)
+    )]
+    pub(crate) fn ensure_loaded() -> Result<()> {
+        #[cfg(all(feature = "dynamic", not(feature = "mock")))]
+        sys::load().map_err(Self::Load)?;
+
+        Ok(())
+    }
+
+    /// Converts a libuiohook status code into a `Result`.
+    pub(crate) fn check(code: c_int) -> Result<()> {
+        Self::from_code(code).map_or(Ok(()), Err)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyRunning => f.write_str("a hook is already running in this process"),
+            Self::Spawn(_) => f.write_str("failed to spawn the hook thread"),
+            Self::Timeout => f.write_str("timed out waiting for the hook"),
+            Self::Unpostable(kind) => write!(f, "libuiohook cannot post {kind:?} events"),
+            #[cfg(feature = "dynamic")]
+            Self::Load(_) => f.write_str("failed to load libuiohook"),
+            Self::Failure => f.write_str("libuiohook reported an unspecified failure"),
+            Self::OutOfMemory => f.write_str("libuiohook ran out of memory"),
+            Self::X11(_) => f.write_str("the libuiohook X11 backend failed"),
+            Self::Windows(_) => f.write_str("the libuiohook Windows backend failed"),
+            Self::Darwin(_) => f.write_str("the libuiohook Darwin backend failed"),
+            Self::Unknown(code) => write!(f, "libuiohook returned unknown status code {code:#04x}"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Spawn(err) => Some(err),
+            #[cfg(feature = "dynamic")]
+            Self::Load(err) => Some(err),
+            Self::X11(err) => Some(err),
+            Self::Windows(err) => Some(err),
+            Self::Darwin(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Errors produced by the X11 backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum X11Error {
+    /// `UIOHOOK_ERROR_X_OPEN_DISPLAY`.
+    OpenDisplay,
+    /// `UIOHOOK_ERROR_X_RECORD_NOT_FOUND`.
+    RecordNotFound,
+    /// `UIOHOOK_ERROR_X_RECORD_ALLOC_RANGE`.
+    RecordAllocRange,
+    /// `UIOHOOK_ERROR_X_RECORD_CREATE_CONTEXT`.
+    RecordCreateContext,
+    /// `UIOHOOK_ERROR_X_RECORD_ENABLE_CONTEXT`.
+    RecordEnableContext,
+    /// `UIOHOOK_ERROR_X_RECORD_GET_CONTEXT`.
+    RecordGetContext,
+}
+
+impl X11Error {
+    /// Returns the libuiohook status code of the error.
+    #[must_use]
+    pub fn code(self) -> c_int {
+        match self {
+            Self::OpenDisplay => sys::UIOHOOK_ERROR_X_OPEN_DISPLAY,
+            Self::RecordNotFound => sys::UIOHOOK_ERROR_X_RECORD_NOT_FOUND,
+            Self::RecordAllocRange => sys::UIOHOOK_ERROR_X_RECORD_ALLOC_RANGE,
+            Self::RecordCreateContext => sys::UIOHOOK_ERROR_X_RECORD_CREATE_CONTEXT,
+            Self::RecordEnableContext => sys::UIOHOOK_ERROR_X_RECORD_ENABLE_CONTEXT,
+            Self::RecordGetContext => sys::UIOHOOK_ERROR_X_RECORD_GET_CONTEXT,
+        }
+    }
+
+    /// Returns the name of the X11 subsystem that failed.
+    #[must_use]
+    pub fn subsystem(self) -> &'static str {
+        match self {
+            Self::OpenDisplay => "Xlib",
+            _ => "XRecord",
+        }
+    }
+}
+
+impl fmt::Display for X11Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::OpenDisplay => "failed to open the X display; check that DISPLAY is set",
+            Self::RecordNotFound => "XRecord extension not available on this display",
+            Self::RecordAllocRange => "failed to allocate an XRecord range",
+            Self::RecordCreateContext => "failed to create the XRecord context",
+            Self::RecordEnableContext => "failed to enable the XRecord context",
+            Self::RecordGetContext => "failed to query the XRecord context",
+        })
+    }
+}
+
+impl error::Error for X11Error {}
+
+/// Errors produced by the Windows backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WindowsError {
+    /// `UIOHOOK_ERROR_SET_WINDOWS_HOOK_EX`.
+    SetWindowsHookEx,
+    /// `UIOHOOK_ERROR_GET_MODULE_HANDLE`.
+    GetModuleHandle,
+}
+
+impl WindowsError {
+    /// Returns the libuiohook status code of the error.
+    #[must_use]
+    pub fn code(self) -> c_int {
+        match self {
+            Self::SetWindowsHookEx => sys::UIOHOOK_ERROR_SET_WINDOWS_HOOK_EX,
+            Self::GetModuleHandle => sys::UIOHOOK_ERROR_GET_MODULE_HANDLE,
+        }
+    }
+
+    /// Returns the name of the Win32 subsystem that failed.
+    #[must_use]
+    pub fn subsystem(self) -> &'static str {
+        match self {
+            Self::SetWindowsHookEx => "Win32 hooks",
+            Self::GetModuleHandle => "Win32 module loader",
+        }
+    }
+}
+
+impl fmt::Display for WindowsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::SetWindowsHookEx => "SetWindowsHookEx failed to install the low level hooks",
+            Self::GetModuleHandle => "GetModuleHandle failed to find the module to hook from",
+        })
+    }
+}
+
+impl error::Error for WindowsError {}
+
+/// Errors produced by the Darwin backend.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DarwinError {
+    /// `UIOHOOK_ERROR_AXAPI_DISABLED`.
+    AxApiDisabled,
+    /// `UIOHOOK_ERROR_CREATE_EVENT_PORT`.
+    CreateEventPort,
+    /// `UIOHOOK_ERROR_CREATE_RUN_LOOP_SOURCE`.
+    CreateRunLoopSource,
+    /// `UIOHOOK_ERROR_GET_RUNLOOP`.
+    GetRunLoop,
+    /// `UIOHOOK_ERROR_CREATE_OBSERVER`.
+    CreateObserver,
+}
+
+impl DarwinError {
+    /// Returns the libuiohook status code of the error.
+    #[must_use]
+    pub fn code(self) -> c_int {
+        match self {
+            Self::AxApiDisabled => sys::UIOHOOK_ERROR_AXAPI_DISABLED,
+            Self::CreateEventPort => sys::UIOHOOK_ERROR_CREATE_EVENT_PORT,
+            Self::CreateRunLoopSource => sys::UIOHOOK_ERROR_CREATE_RUN_LOOP_SOURCE,
+            Self::GetRunLoop => sys::UIOHOOK_ERROR_GET_RUNLOOP,
+            Self::CreateObserver => sys::UIOHOOK_ERROR_CREATE_OBSERVER,
+        }
+    }
+
+    /// Returns the name of the macOS subsystem that failed.
+    #[must_use]
+    pub fn subsystem(self) -> &'static str {
+        match self {
+            Self::AxApiDisabled => "Accessibility API",
+            Self::CreateEventPort => "Quartz event taps",
+            Self::CreateRunLoopSource | Self::GetRunLoop | Self::CreateObserver => {
+                "CoreFoundation run loop"
+            }
+        }
+    }
+}
+
+impl fmt::Display for DarwinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::AxApiDisabled => {
+                "accessibility API is disabled; grant this application access in the \
+                 Accessibility section of the Privacy & Security settings"
+            }
+            Self::CreateEventPort => "failed to create the event tap",
+            Self::CreateRunLoopSource => "failed to create a run loop source for the event tap",
+            Self::GetRunLoop => "failed to get the current run loop",
+            Self::CreateObserver => "failed to create the run loop observer",
+        })
+    }
+}
+
+impl error::Error for DarwinError {}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+
+    use super::*;
+
+    #[test]
+    fn maps_every_status_code() {
+        let codes = [
+            (sys::UIOHOOK_FAILURE, "Failure"),
+            (sys::UIOHOOK_ERROR_OUT_OF_MEMORY, "OutOfMemory"),
+            (sys::UIOHOOK_ERROR_X_OPEN_DISPLAY, "X11(OpenDisplay)"),
+            (sys::UIOHOOK_ERROR_X_RECORD_NOT_FOUND, "X11(RecordNotFound)"),
+            (
+                sys::UIOHOOK_ERROR_X_RECORD_ALLOC_RANGE,
+                "X11(RecordAllocRange)",
+            ),
+            (
+                sys::UIOHOOK_ERROR_X_RECORD_CREATE_CONTEXT,
+                "X11(RecordCreateContext)",
+            ),
+            (
+                sys::UIOHOOK_ERROR_X_RECORD_ENABLE_CONTEXT,
+                "X11(RecordEnableContext)",
+            ),
+            (
+                sys::UIOHOOK_ERROR_X_RECORD_GET_CONTEXT,
+                "X11(RecordGetContext)",
+            ),
+            (
+                sys::UIOHOOK_ERROR_SET_WINDOWS_HOOK_EX,
+                "Windows(SetWindowsHookEx)",
+            ),
+            (
+                sys::UIOHOOK_ERROR_GET_MODULE_HANDLE,
+                "Windows(GetModuleHandle)",
+            ),
+            (sys::UIOHOOK_ERROR_AXAPI_DISABLED, "Darwin(AxApiDisabled)"),
+            (
+                sys::UIOHOOK_ERROR_CREATE_EVENT_PORT,
+                "Darwin(CreateEventPort)",
+            ),
+            (
+                sys::UIOHOOK_ERROR_CREATE_RUN_LOOP_SOURCE,
+                "Darwin(CreateRunLoopSource)",
+            ),
+            (sys::UIOHOOK_ERROR_GET_RUNLOOP, "Darwin(GetRunLoop)"),
+            (sys::UIOHOOK_ERROR_CREATE_OBSERVER, "Darwin(CreateObserver)"),
+            (0x7f, "Unknown(127)"),
+        ];
+
+        for (code, variant) in codes {
+            let err = Error::check(code).unwrap_err();
+            assert_eq!(format!("{err:?}"), variant);
+            assert_eq!(err.code(), Some(code), "{variant}");
+            assert_eq!(Error::from_code(code).unwrap().code(), Some(code));
+        }
+
+        assert!(Error::check(sys::UIOHOOK_SUCCESS).is_ok());
+        assert!(Error::from_code(sys::UIOHOOK_SUCCESS).is_none());
+    }
+
+    #[test]
+    fn exposes_platform_errors_as_sources() {
+        let err = Error::X11(X11Error::OpenDisplay);
+
+        assert_eq!(err.to_string(), "the libuiohook X11 backend failed");
+        assert_eq!(
+            err.source().unwrap().to_string(),
+            X11Error::OpenDisplay.to_string()
+        );
+        assert_eq!(err.subsystem(), Some("Xlib"));
+
+        for err in [
+            Error::Windows(WindowsError::GetModuleHandle),
+            Error::Darwin(DarwinError::GetRunLoop),
+        ] {
+            assert!(err.source().is_some(), "{err:?}");
+        }
+        assert!(Error::Failure.source().is_none());
+        assert!(Error::Timeout.code().is_none());
+    }
+}
@@ -0,0 +1,584 @@
+//! Typed events decoded from [`uiohook_event`].
+//!
+//! This is the only module that reads or writes the `input_event_data` union; everything else
+//! goes through the [`Event`] conversions.
+
+use std::{error, fmt, os::raw::c_uint};
+
+use crate::{
+    key::Key,
+    modifiers::Modifiers,
+    sys::{self, event_type as et, uiohook_event},
+};
+
+/// An event produced by the hook.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Event {
+    /// The hook was installed and started delivering events.
+    HookEnabled(HookEvent),
+    /// The hook was removed and will not deliver any more events.
+    HookDisabled(HookEvent),
+    /// A key was pressed.
+    KeyPressed(KeyboardEvent),
+    /// A key was released.
+    KeyReleased(KeyboardEvent),
+    /// A key press produced a character.
+    KeyTyped(KeyboardEvent),
+    /// A mouse button was pressed.
+    MousePressed(MouseEvent),
+    /// A mouse button was released.
+    MouseReleased(MouseEvent),
+    /// A mouse button was pressed and released without moving.
+    MouseClicked(MouseEvent),
+    /// The mouse moved without any button held.
+    MouseMoved(MouseEvent),
+    /// The mouse moved while a button was held.
+    MouseDragged(MouseEvent),
+    /// The mouse wheel was scrolled.
+    Wheel(WheelEvent),
+}
+
+impl Event {
+    /// Returns the kind of the event.
+    #[must_use]
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::HookEnabled(_) => EventKind::HookEnabled,
+            Self::HookDisabled(_) => EventKind::HookDisabled,
+            Self::KeyPressed(_) => EventKind::KeyPressed,
+            Self::KeyReleased(_) => EventKind::KeyReleased,
+            Self::KeyTyped(_) => EventKind::KeyTyped,
+            Self::MousePressed(_) => EventKind::MousePressed,
+            Self::MouseReleased(_) => EventKind::MouseReleased,
+            Self::MouseClicked(_) => EventKind::MouseClicked,
+            Self::MouseMoved(_) => EventKind::MouseMoved,
+            Self::MouseDragged(_) => EventKind::MouseDragged,
+            Self::Wheel(_) => EventKind::Wheel,
+        }
+    }
+
+    /// Returns the time the event was produced, in milliseconds.
+    #[must_use]
+    pub fn time(&self) -> u64 {
+        match self {
+            Self::HookEnabled(event) | Self::HookDisabled(event) => event.time,
+            Self::KeyPressed(event) | Self::KeyReleased(event) | Self::KeyTyped(event) => {
+                event.time
+            }
+            Self::MousePressed(event)
+            | Self::MouseReleased(event)
+            | Self::MouseClicked(event)
+            | Self::MouseMoved(event)
+            | Self::MouseDragged(event) => event.time,
+            Self::Wheel(event) => event.time,
+        }
+    }
+
+    /// Returns the modifiers that were held when the event was produced.
+    #[must_use]
+    pub fn mask(&self) -> Modifiers {
+        match self {
+            Self::HookEnabled(event) | Self::HookDisabled(event) => event.mask,
+            Self::KeyPressed(event) | Self::KeyReleased(event) | Self::KeyTyped(event) => {
+                event.mask
+            }
+            Self::MousePressed(event)
+            | Self::MouseReleased(event)
+            | Self::MouseClicked(event)
+            | Self::MouseMoved(event)
+            | Self::MouseDragged(event) => event.mask,
+            Self::Wheel(event) => event.mask,
+        }
+    }
+}
+
+/// The kind of an [`Event`], without its payload.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EventKind {
+    /// [`Event::HookEnabled`], `EVENT_HOOK_ENABLED`.
+    HookEnabled,
+    /// [`Event::HookDisabled`], `EVENT_HOOK_DISABLED`.
+    HookDisabled,
+    /// [`Event::KeyPressed`], `EVENT_KEY_PRESSED`.
+    KeyPressed,
+    /// [`Event::KeyReleased`], `EVENT_KEY_RELEASED`.
+    KeyReleased,
+    /// [`Event::KeyTyped`], `EVENT_KEY_TYPED`.
+    KeyTyped,
+    /// [`Event::MousePressed`], `EVENT_MOUSE_PRESSED`.
+    MousePressed,
+    /// [`Event::MouseReleased`], `EVENT_MOUSE_RELEASED`.
+    MouseReleased,
+    /// [`Event::MouseClicked`], `EVENT_MOUSE_CLICKED`.
+    MouseClicked,
+    /// [`Event::MouseMoved`], `EVENT_MOUSE_MOVED`.
+    MouseMoved,
+    /// [`Event::MouseDragged`], `EVENT_MOUSE_DRAGGED`.
+    MouseDragged,
+    /// [`Event::Wheel`], `EVENT_MOUSE_WHEEL`.
+    Wheel,
+}
+
+impl EventKind {
+    /// Converts a raw `event_type`, returning `None` for unknown types.
+    #[must_use]
+    pub fn from_raw(r#type: sys::event_type) -> Option<Self> {
+        Some(match r#type {
+            et::EVENT_HOOK_ENABLED => Self::HookEnabled,
+            et::EVENT_HOOK_DISABLED => Self::HookDisabled,
+            et::EVENT_KEY_PRESSED => Self::KeyPressed,
+            et::EVENT_KEY_RELEASED => Self::KeyReleased,
+            et::EVENT_KEY_TYPED => Self::KeyTyped,
+            et::EVENT_MOUSE_PRESSED => Self::MousePressed,
+            et::EVENT_MOUSE_RELEASED => Self::MouseReleased,
+            et::EVENT_MOUSE_CLICKED => Self::MouseClicked,
+            et::EVENT_MOUSE_MOVED => Self::MouseMoved,
+            et::EVENT_MOUSE_DRAGGED => Self::MouseDragged,
+            et::EVENT_MOUSE_WHEEL => Self::Wheel,
+            _ => return None,
+        })
+    }
+
+    /// Returns the raw `event_type` of the kind.
+    #[must_use]
+    pub fn to_raw(self) -> sys::event_type {
+        match self {
+            Self::HookEnabled => et::EVENT_HOOK_ENABLED,
+            Self::HookDisabled => et::EVENT_HOOK_DISABLED,
+            Self::KeyPressed => et::EVENT_KEY_PRESSED,
+            Self::KeyReleased => et::EVENT_KEY_RELEASED,
+            Self::KeyTyped => et::EVENT_KEY_TYPED,
+            Self::MousePressed => et::EVENT_MOUSE_PRESSED,
+            Self::MouseReleased => et::EVENT_MOUSE_RELEASED,
+            Self::MouseClicked => et::EVENT_MOUSE_CLICKED,
+            Self::MouseMoved => et::EVENT_MOUSE_MOVED,
+            Self::MouseDragged => et::EVENT_MOUSE_DRAGGED,
+            Self::Wheel => et::EVENT_MOUSE_WHEEL,
+        }
+    }
+}
+
+/// Payload of [`Event::HookEnabled`] and [`Event::HookDisabled`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct HookEvent {
+    /// Time the event was produced, in milliseconds.
+    pub time: u64,
+    /// Modifiers held when the event was produced.
+    pub mask: Modifiers,
+}
+
+/// Payload of the keyboard events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyboardEvent {
+    /// Time the event was produced, in milliseconds.
+    pub time: u64,
+    /// Modifiers held when the event was produced.
+    pub mask: Modifiers,
+    /// Virtual key that changed state.
+    pub key: Key,
+    /// Platform specific key code.
+    pub rawcode: u16,
+    /// UTF-16 code unit produced by the key, or `CHAR_UNDEFINED`.
+    pub keychar: u16,
+}
+
+impl KeyboardEvent {
+    /// Returns the character produced by the key, if any.
+    ///
+    /// Only [`Event::KeyTyped`] carries a character, and code units that are part of a surrogate
+    /// pair are not representable as a [`char`] on their own.
+    #[must_use]
+    pub fn char(&self) -> Option<char> {
+        if self.keychar == sys::CHAR_UNDEFINED {
+            return None;
+        }
+
+        char::from_u32(self.keychar.into())
+    }
+}
+
+/// A mouse button.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MouseButton {
+    /// The left button, `MOUSE_BUTTON1`.
+    Left,
+    /// The right button, `MOUSE_BUTTON2`.
+    Right,
+    /// The middle button, `MOUSE_BUTTON3`.
+    Middle,
+    /// The first extra button, `MOUSE_BUTTON4`.
+    Extra1,
+    /// The second extra button, `MOUSE_BUTTON5`.
+    Extra2,
+    /// Any other button reported by the platform.
+    Other(u16),
+}
+
+impl MouseButton {
+    /// Converts a raw `MOUSE_*` button, returning `None` for `MOUSE_NOBUTTON`.
+    #[must_use]
+    pub fn from_raw(button: u16) -> Option<Self> {
+        Some(match button {
+            sys::MOUSE_NOBUTTON => return None,
+            sys::MOUSE_BUTTON1 => Self::Left,
+            sys::MOUSE_BUTTON2 => Self::Right,
+            sys::MOUSE_BUTTON3 => Self::Middle,
+            sys::MOUSE_BUTTON4 => Self::Extra1,
+            sys::MOUSE_BUTTON5 => Self::Extra2,
+            other => Self::Other(other),
+        })
+    }
+
+    /// Returns the raw `MOUSE_*` value of the button.
+    #[must_use]
+    pub fn to_raw(self) -> u16 {
+        match self {
+            Self::Left => sys::MOUSE_BUTTON1,
+            Self::Right => sys::MOUSE_BUTTON2,
+            Self::Middle => sys::MOUSE_BUTTON3,
+            Self::Extra1 => sys::MOUSE_BUTTON4,
+            Self::Extra2 => sys::MOUSE_BUTTON5,
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// Payload of the mouse button and motion events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MouseEvent {
+    /// Time the event was produced, in milliseconds.
+    pub time: u64,
+    /// Modifiers held when the event was produced.
+    pub mask: Modifiers,
+    /// Button that changed state, or `None` for motion events.
+    pub button: Option<MouseButton>,
+    /// Number of consecutive clicks of `button`.
+    pub clicks: u16,
+    /// Horizontal position of the cursor.
+    pub x: i16,
+    /// Vertical position of the cursor.
+    pub y: i16,
+}
+
+/// How the amount of a [`WheelEvent`] should be interpreted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ScrollKind {
+    /// Scroll by units, usually lines; `WHEEL_UNIT_SCROLL`.
+    Unit,
+    /// Scroll by blocks, usually pages; `WHEEL_BLOCK_SCROLL`.
+    Block,
+    /// Any other scroll type reported by the platform.
+    Other(u8),
+}
+
+impl ScrollKind {
+    /// Converts a raw `WHEEL_*_SCROLL` type.
+    #[must_use]
+    pub fn from_raw(kind: u8) -> Self {
+        match kind {
+            sys::WHEEL_UNIT_SCROLL => Self::Unit,
+            sys::WHEEL_BLOCK_SCROLL => Self::Block,
+            other => Self::Other(other),
+        }
+    }
+
+    /// Returns the raw `WHEEL_*_SCROLL` value of the type.
+    #[must_use]
+    pub fn to_raw(self) -> u8 {
+        match self {
+            Self::Unit => sys::WHEEL_UNIT_SCROLL,
+            Self::Block => sys::WHEEL_BLOCK_SCROLL,
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// The axis a [`WheelEvent`] scrolls along.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum WheelDirection {
+    /// `WHEEL_VERTICAL_DIRECTION`.
+    Vertical,
+    /// `WHEEL_HORIZONTAL_DIRECTION`.
+    Horizontal,
+    /// Any other direction reported by the platform.
+    Other(u8),
+}
+
+impl WheelDirection {
+    /// Converts a raw `WHEEL_*_DIRECTION`.
+    #[must_use]
+    pub fn from_raw(direction: u8) -> Self {
+        match direction {
+            sys::WHEEL_VERTICAL_DIRECTION => Self::Vertical,
+            sys::WHEEL_HORIZONTAL_DIRECTION => Self::Horizontal,
+            other => Self::Other(other),
+        }
+    }
+
+    /// Returns the raw `WHEEL_*_DIRECTION` value of the direction.
+    #[must_use]
+    pub fn to_raw(self) -> u8 {
+        match self {
+            Self::Vertical => sys::WHEEL_VERTICAL_DIRECTION,
+            Self::Horizontal => sys::WHEEL_HORIZONTAL_DIRECTION,
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// Payload of [`Event::Wheel`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WheelEvent {
+    /// Time the event was produced, in milliseconds.
+    pub time: u64,
+    /// Modifiers held when the event was produced.
+    pub mask: Modifiers,
+    /// Number of consecutive wheel clicks.
+    pub clicks: u16,
+    /// Horizontal position of the cursor.
+    pub x: i16,
+    /// Vertical position of the cursor.
+    pub y: i16,
+    /// How `amount` should be interpreted.
+    pub kind: ScrollKind,
+    /// Number of units or blocks to scroll per click.
+    pub amount: u16,
+    /// Signed number of wheel clicks; negative values scroll up or left.
+    pub rotation: i16,
+    /// Axis of the scroll.
+    pub direction: WheelDirection,
+}
+
+/// The error returned when a [`uiohook_event`] cannot be converted into an [`Event`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum InvalidEvent {
+    /// The event has an unknown `event_type`; its data was not read.
+    Type(c_uint),
+}
+
+impl fmt::Display for InvalidEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Type(r#type) => write!(f, "unknown event type {type}"),
+        }
+    }
+}
+
+impl error::Error for InvalidEvent {}
+
+impl TryFrom<&uiohook_event> for Event {
+    type Error = InvalidEvent;
+
+    fn try_from(raw: &uiohook_event) -> Result<Self, Self::Error> {
+        let (time, mask) = (raw.time, Modifiers::from_bits_retain(raw.mask));
+
+        let keyboard = || {
+            // SAFETY: only called for keyboard event types, whose data is `keyboard`.
+            let data = unsafe { raw.data.keyboard };
+
+            KeyboardEvent {
+                time,
+                mask,
+                key: Key::from_code(data.keycode),
+                rawcode: data.rawcode,
+                keychar: data.keychar,
+            }
+        };
+
+        let mouse = || {
+            // SAFETY: only called for mouse event types, whose data is `mouse`.
+            let data = unsafe { raw.data.mouse };
+
+            MouseEvent {
+                time,
+                mask,
+                button: MouseButton::from_raw(data.button),
+                clicks: data.clicks,
+                x: data.x,
+                y: data.y,
+            }
+        };
+
+        let kind = EventKind::from_raw(raw.r#type).ok_or(InvalidEvent::Type(raw.r#type.0))?;
+
+        Ok(match kind {
+            EventKind::HookEnabled => Self::HookEnabled(HookEvent { time, mask }),
+            EventKind::HookDisabled => Self::HookDisabled(HookEvent { time, mask }),
+
+            EventKind::KeyTyped => Self::KeyTyped(keyboard()),
+            EventKind::KeyPressed => Self::KeyPressed(keyboard()),
+            EventKind::KeyReleased => Self::KeyReleased(keyboard()),
+
+            EventKind::MouseClicked => Self::MouseClicked(mouse()),
+            EventKind::MousePressed => Self::MousePressed(mouse()),
+            EventKind::MouseReleased => Self::MouseReleased(mouse()),
+            EventKind::MouseMoved => Self::MouseMoved(mouse()),
+            EventKind::MouseDragged => Self::MouseDragged(mouse()),
+
+            EventKind::Wheel => {
+                // SAFETY: the event type is `EVENT_MOUSE_WHEEL`, whose data is `wheel`.
+                let data = unsafe { raw.data.wheel };
+
+                Self::Wheel(WheelEvent {
+                    time,
+                    mask,
+                    clicks: data.clicks,
+                    x: data.x,
+                    y: data.y,
+                    kind: ScrollKind::from_raw(data.r#type),
+                    amount: data.amount,
+                    rotation: data.rotation,
+                    direction: WheelDirection::from_raw(data.direction),
+                })
+            }
+        })
+    }
+}
+
+impl From<Event> for uiohook_event {
+    fn from(event: Event) -> Self {
+        let keyboard = |event: KeyboardEvent| sys::input_event_data {
+            keyboard: sys::keyboard_event_data {
+                keycode: event.key.code(),
+                rawcode: event.rawcode,
+                keychar: event.keychar,
+            },
+        };
+
+        let mouse = |event: MouseEvent| sys::input_event_data {
+            mouse: sys::mouse_event_data {
+                button: event
+                    .button
+                    .map_or(sys::MOUSE_NOBUTTON, MouseButton::to_raw),
+                clicks: event.clicks,
+                x: event.x,
+                y: event.y,
+            },
+        };
+
+        let data = match event {
+            // The hook events carry no data, but the union still has to be initialized.
+            Event::HookEnabled(_) | Event::HookDisabled(_) => zeroed_data(),
+
+            Event::KeyTyped(event) | Event::KeyPressed(event) | Event::KeyReleased(event) => {
+                keyboard(event)
+            }
+
+            Event::MouseClicked(event)
+            | Event::MousePressed(event)
+            | Event::MouseReleased(event)
+            | Event::MouseMoved(event)
+            | Event::MouseDragged(event) => mouse(event),
+
+            Event::Wheel(event) => sys::input_event_data {
+                wheel: sys::mouse_wheel_event_data {
+                    clicks: event.clicks,
+                    x: event.x,
+                    y: event.y,
+                    r#type: event.kind.to_raw(),
+                    amount: event.amount,
+                    rotation: event.rotation,
+                    direction: event.direction.to_raw(),
+                },
+            },
+        };
+
+        Self {
+            r#type: event.kind().to_raw(),
+            time: event.time(),
+            mask: event.mask().bits(),
+            reserved: 0,
+            data,
+        }
+    }
+}
+
+fn zeroed_data() -> sys::input_event_data {
+    sys::input_event_data {
+        wheel: sys::mouse_wheel_event_data {
+            clicks: 0,
+            x: 0,
+            y: 0,
+            r#type: 0,
+            amount: 0,
+            rotation: 0,
+            direction: 0,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::EventBuilder;
+
+    /// One event of every kind, with every field set.
+    fn events() -> Vec<Event> {
+        let mask = Modifiers::SHIFT_LEFT | Modifiers::NUM_LOCK;
+        let button = |builder: EventBuilder| builder.position(-3, 4).clicks(2);
+
+        [
+            EventBuilder::hook_enabled(),
+            EventBuilder::hook_disabled(),
+            EventBuilder::key_pressed(Key::A).rawcode(38),
+            EventBuilder::key_released(Key::ShiftLeft).rawcode(50),
+            EventBuilder::key_typed('é'),
+            button(EventBuilder::mouse_pressed(MouseButton::Left)),
+            button(EventBuilder::mouse_released(MouseButton::Other(9))),
+            button(EventBuilder::mouse_clicked(MouseButton::Extra2)),
+            EventBuilder::mouse_moved(100, -200),
+            EventBuilder::mouse_dragged(MouseButton::Right, 5, 6),
+            EventBuilder::wheel(WheelDirection::Vertical, -2).amount(ScrollKind::Block, 1),
+            EventBuilder::wheel(WheelDirection::Horizontal, 1).position(7, 8),
+        ]
+        .into_iter()
+        .map(|builder| builder.mask(mask).at(1234).build())
+        .collect()
+    }
+
+    #[test]
+    fn converts_every_kind() {
+        for event in events() {
+            let raw = uiohook_event::from(event);
+            assert_eq!(raw.r#type, event.kind().to_raw());
+            assert_eq!(EventKind::from_raw(raw.r#type), Some(event.kind()));
+            assert_eq!(raw.time, 1234);
+            assert_eq!(raw.mask, event.mask().bits());
+
+            assert_eq!(Event::try_from(&raw), Ok(event));
+            assert_eq!(uiohook_event::from(Event::try_from(&raw).unwrap()), raw);
+        }
+    }
+
+    #[test]
+    fn keeps_unknown_values() {
+        let mut raw = EventBuilder::wheel(WheelDirection::Vertical, 1).build_raw();
+        raw.data.wheel.r#type = 7;
+        raw.data.wheel.direction = 9;
+
+        let Ok(Event::Wheel(wheel)) = Event::try_from(&raw) else {
+            panic!("the wheel event was not converted");
+        };
+        assert_eq!(wheel.kind, ScrollKind::Other(7));
+        assert_eq!(wheel.direction, WheelDirection::Other(9));
+        assert_eq!(uiohook_event::from(Event::Wheel(wheel)), raw);
+
+        let mut raw = EventBuilder::mouse_pressed(MouseButton::Left).build_raw();
+        raw.data.mouse.button = sys::MOUSE_NOBUTTON;
+        let Ok(Event::MousePressed(mouse)) = Event::try_from(&raw) else {
+            panic!("the mouse event was not converted");
+        };
+        assert_eq!(mouse.button, None);
+    }
+
+    #[test]
+    fn rejects_unknown_types() {
+        let mut raw = EventBuilder::key_pressed(Key::A).build_raw();
+        raw.r#type = sys::event_type(99);
+
+        assert_eq!(Event::try_from(&raw), Err(InvalidEvent::Type(99)));
+        assert_eq!(EventKind::from_raw(raw.r#type), None);
+        assert_eq!(InvalidEvent::Type(99).to_string(), "unknown event type 99");
+    }
+}
@@ -0,0 +1,659 @@
+use std::{
+    any::Any,
+    collections::VecDeque,
+    fmt,
+    os::raw::c_int,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Condvar, Mutex, MutexGuard, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    error::{Error, Result},
+    event::Event,
+    sys,
+};
+
+/// Receives the raw events; it may set `reserved` to consume them with libuiohook 1.3.
+type Dispatcher = Box<dyn FnMut(&mut sys::uiohook_event) + Send>;
+
+/// Receives the status returned by `hook_run`, on the hook thread.
+type Finisher = Box<dyn FnOnce(c_int) + Send>;
+
+/// Set while a [`HookHandle`] is alive; libuiohook only supports a single hook per process.
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set when the handle was dropped from within the dispatcher, leaving the hook thread to clean up
+/// after itself once `hook_run` returns.
+static DETACHED: AtomicBool = AtomicBool::new(false);
+
+/// The closure `dispatch_proc` forwards events to.
+///
+/// libuiohook 1.2 has no way to pass state to the dispatcher, so it has to be global.
+#[cfg(not(feature = "v1_3"))]
+static DISPATCHER: Mutex<Option<Dispatcher>> = Mutex::new(None);
+
+/// The value of `reserved` that keeps an event from the rest of the system.
+#[cfg(feature = "v1_3")]
+const CONSUMED: u16 = 0x01;
+
+/// How long to wait between `hook_stop` attempts while the hook is still being installed.
+const STOP_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+
+#[cfg(not(feature = "v1_3"))]
+extern "C" fn dispatch_proc(event: *const sys::uiohook_event) {
+    // SAFETY: libuiohook passes either null or a pointer to an event that lives for the whole
+    // call.
+    let Some(event) = (unsafe { event.as_ref() }) else {
+        return;
+    };
+
+    let mut dispatcher = DISPATCHER.lock().unwrap_or_else(PoisonError::into_inner);
+
+    // libuiohook 1.2 cannot consume events, so the dispatcher gets a copy to write to.
+    if let Some(dispatcher) = dispatcher.as_mut() {
+        dispatcher(&mut event.clone());
+    }
+}
+
+#[cfg(feature = "v1_3")]
+extern "C" fn dispatch_proc(event: *mut sys::uiohook_event, user_data: *mut std::ffi::c_void) {
+    // SAFETY: `user_data` is the dispatcher registered by `run`, which outlives `hook_run` and is
+    // only ever accessed from the hook thread.
+    let dispatcher = unsafe { user_data.cast::<Option<Dispatcher>>().as_mut() };
+
+    // SAFETY: libuiohook passes either null or a pointer to an event that lives for the whole
+    // call.
+    if let (Some(Some(dispatcher)), Some(event)) = (dispatcher, unsafe { event.as_mut() }) {
+        dispatcher(event);
+    }
+}
+
+/// Registers `dispatcher` and runs the hook on the current thread until it is stopped.
+#[cfg(not(feature = "v1_3"))]
+fn run(dispatcher: Option<Dispatcher>) -> c_int {
+    *DISPATCHER.lock().unwrap_or_else(PoisonError::into_inner) = dispatcher;
+
+    // SAFETY: `dispatch_proc` is a valid dispatcher for the lifetime of the program, and `ACTIVE`
+    // guarantees that no other thread is running the hook.
+    let status = unsafe {
+        sys::hook_set_dispatch_proc(dispatch_proc);
+        sys::hook_run()
+    };
+
+    DISPATCHER
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take();
+
+    status
+}
+
+/// Registers `dispatcher` and runs the hook on the current thread until it is stopped.
+#[cfg(feature = "v1_3")]
+fn run(mut dispatcher: Option<Dispatcher>) -> c_int {
+    let user_data = std::ptr::addr_of_mut!(dispatcher).cast();
+
+    // SAFETY: `dispatcher` outlives `hook_run` and is unregistered before it is dropped, and
+    // `ACTIVE` guarantees that no other thread is running the hook.
+    unsafe {
+        sys::hook_set_dispatch_proc(dispatch_proc, user_data);
+        let status = sys::hook_run();
+        sys::hook_set_dispatch_proc(dispatch_proc, std::ptr::null_mut());
+
+        status
+    }
+}
+
+/// Entry point for installing the keyboard and mouse hook.
+///
+/// Only one hook can be active per process; see [`HookBuilder::spawn`].
+#[derive(Debug)]
+pub struct Hook {
+    _private: (),
+}
+
+impl Hook {
+    /// Creates a builder to configure and spawn a new hook.
+    pub fn builder() -> HookBuilder {
+        HookBuilder::default()
+    }
+}
+
+/// What to do when the dispatcher panics.
+///
+/// The panic is caught before it can unwind into libuiohook either way, and its payload is kept
+/// for [`HookHandle::take_panic`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PanicPolicy {
+    /// Drop the dispatcher and stop the hook.
+    #[default]
+    StopHook,
+    /// Drop the dispatcher but keep the hook running, so input is still delivered to the rest of
+    /// the system.
+    ///
+    /// The dispatcher is the single closure the builder ends up with: after
+    /// [`HookBuilder::on_bus`] or [`HookBuilder::on_hotkeys`], one panicking subscriber or hotkey
+    /// callback stops the delivery to all of them. Use [`PanicPolicy::Continue`] to keep the
+    /// others.
+    DropHandler,
+    /// Keep calling the dispatcher for the following events.
+    Continue,
+}
+
+/// Whether an event goes on to the rest of the system, as decided by the closure set with
+/// [`HookBuilder::intercept`].
+#[cfg(feature = "v1_3")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Delivery {
+    /// Deliver the event as usual.
+    #[default]
+    Forward,
+    /// Keep the event from the rest of the system.
+    Consume,
+}
+
+/// Configures a hook before running it on a dedicated thread.
+#[derive(Default)]
+#[must_use]
+pub struct HookBuilder {
+    dispatcher: Option<Dispatcher>,
+    finisher: Option<Finisher>,
+    thread_name: Option<String>,
+    panic_policy: PanicPolicy,
+}
+
+impl HookBuilder {
+    /// Sets the closure that receives every event produced by the hook.
+    ///
+    /// The closure runs on the hook thread, so it should return quickly; slow dispatchers delay
+    /// the delivery of input to the rest of the system. Events that cannot be decoded into an
+    /// [`Event`] are skipped; use [`HookBuilder::on_raw_event`] to observe them.
+    pub fn on_event<F>(self, mut dispatcher: F) -> Self
+    where
+        F: FnMut(Event) + Send + 'static,
+    {
+        self.on_raw_event(move |event| {
+            if let Ok(event) = Event::try_from(event) {
+                dispatcher(event);
+            }
+        })
+    }
+
+    /// Sets the closure that receives every raw event produced by the hook.
+    ///
+    /// Replaces any closure set by [`HookBuilder::on_event`].
+    pub fn on_raw_event<F>(mut self, mut dispatcher: F) -> Self
+    where
+        F: FnMut(&sys::uiohook_event) + Send + 'static,
+    {
+        self.dispatcher = Some(Box::new(move |event| dispatcher(event)));
+        self
+    }
+
+    /// Sets the closure that receives every event produced by the hook and decides whether it
+    /// goes on to the rest of the system.
+    ///
+    /// libuiohook can only consume events on Windows and macOS; the X11 backend delivers them
+    /// regardless. Events that cannot be decoded into an [`Event`] are forwarded, as are the ones
+    /// during which the closure panics. Replaces any closure set by [`HookBuilder::on_event`].
+    #[cfg(feature = "v1_3")]
+    pub fn intercept<F>(self, mut dispatcher: F) -> Self
+    where
+        F: FnMut(Event) -> Delivery + Send + 'static,
+    {
+        self.intercept_raw(move |event| {
+            Event::try_from(event).map_or(Delivery::Forward, &mut dispatcher)
+        })
+    }
+
+    /// Sets the closure that receives every raw event produced by the hook and decides whether it
+    /// goes on to the rest of the system.
+    ///
+    /// See [`HookBuilder::intercept`].
+    #[cfg(feature = "v1_3")]
+    pub fn intercept_raw<F>(mut self, mut dispatcher: F) -> Self
+    where
+        F: FnMut(&sys::uiohook_event) -> Delivery + Send + 'static,
+    {
+        self.dispatcher = Some(Box::new(move |event| {
+            if dispatcher(event) == Delivery::Consume {
+                event.reserved = CONSUMED;
+            }
+        }));
+        self
+    }
+
+    /// Sets what happens when the dispatcher panics.
+    ///
+    /// Defaults to [`PanicPolicy::StopHook`].
+    pub fn panic_policy(mut self, policy: PanicPolicy) -> Self {
+        self.panic_policy = policy;
+        self
+    }
+
+    /// Sets the closure called on the hook thread with the status returned by `hook_run`, before
+    /// the hook can be spawned again.
+    #[cfg(feature = "async")]
+    pub(crate) fn on_finish<F>(mut self, finisher: F) -> Self
+    where
+        F: FnOnce(c_int) + Send + 'static,
+    {
+        self.finisher = Some(Box::new(finisher));
+        self
+    }
+
+    /// Sets the name of the thread running the hook.
+    ///
+    /// Defaults to `"uiohook"`.
+    pub fn thread_name(mut self, name: impl Into<String>) -> Self {
+        self.thread_name = Some(name.into());
+        self
+    }
+
+    /// Registers the dispatcher and runs the hook on a new thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::AlreadyRunning`] if another [`HookHandle`] is still alive, or
+    /// [`Error::Spawn`] if the hook thread could not be created. With the `dynamic` feature,
+    /// returns `Error::Load` if libuiohook is not available.
+    pub fn spawn(self) -> Result<HookHandle> {
+        Error::ensure_loaded()?;
+
+        if ACTIVE
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(Error::AlreadyRunning);
+        }
+
+        let state = Arc::new(State::default());
+
+        let (mut dispatcher, policy) = (self.dispatcher, self.panic_policy);
+        let dispatcher: Dispatcher = Box::new({
+            let state = Arc::clone(&state);
+            move |event| {
+                state.observe(event);
+
+                let Some(handler) = dispatcher.as_mut() else {
+                    return;
+                };
+
+                // Unwinding into libuiohook is undefined behaviour.
+                let Err(payload) = panic::catch_unwind(AssertUnwindSafe(|| handler(event))) else {
+                    return;
+                };
+
+                state.panicked(payload);
+                match policy {
+                    PanicPolicy::StopHook => {
+                        dispatcher = None;
+                        // SAFETY: the hook is running on this very thread.
+                        unsafe { sys::hook_stop() };
+                    }
+                    PanicPolicy::DropHandler => dispatcher = None,
+                    PanicPolicy::Continue => {}
+                }
+            }
+        });
+
+        let finisher = self.finisher;
+        let thread = thread::Builder::new()
+            .name(self.thread_name.unwrap_or_else(|| "uiohook".to_owned()))
+            .spawn({
+                let state = Arc::clone(&state);
+                move || {
+                    let status = run(Some(dispatcher));
+                    state.update(|progress| progress.status = Some(status));
+                    if let Some(finisher) = finisher {
+                        finisher(status);
+                    }
+                    if DETACHED.swap(false, Ordering::AcqRel) {
+                        release();
+                    }
+
+                    status
+                }
+            });
+
+        match thread {
+            Ok(thread) => Ok(HookHandle {
+                thread: Some(thread),
+                state,
+            }),
+            Err(err) => {
+                release();
+                Err(Error::Spawn(err))
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HookBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HookBuilder")
+            .field("dispatcher", &self.dispatcher.as_ref().map(|_| ..))
+            .field("finisher", &self.finisher.as_ref().map(|_| ..))
+            .field("thread_name", &self.thread_name)
+            .field("panic_policy", &self.panic_policy)
+            .finish()
+    }
+}
+
+/// A handle to the running hook.
+///
+/// Dropping the handle stops the hook and waits for its thread to finish; use
+/// [`HookHandle::stop`] to observe errors.
+#[derive(Debug)]
+pub struct HookHandle {
+    thread: Option<JoinHandle<c_int>>,
+    state: Arc<State>,
+}
+
+impl HookHandle {
+    /// Waits up to `timeout` for the hook to be installed, as signalled by
+    /// [`Event::HookEnabled`].
+    ///
+    /// Returns immediately if the hook is already installed. Must not be called from the
+    /// dispatcher, which would wait for itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error reported by `hook_run` as soon as it fails, or [`Error::Timeout`] if the
+    /// hook was not installed in time.
+    pub fn wait_ready(&self, timeout: Duration) -> Result<()> {
+        let progress = self.state.wait(timeout, |progress| {
+            progress.enabled || progress.status.is_some()
+        })?;
+
+        match progress.status {
+            Some(status) if !progress.enabled => Error::check(status),
+            _ => Ok(()),
+        }
+    }
+
+    /// Waits up to `timeout` for the hook to be removed, as signalled by
+    /// [`Event::HookDisabled`].
+    ///
+    /// This does not stop the hook; see [`HookHandle::stop`]. Must not be called from the
+    /// dispatcher, which would wait for itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error reported by `hook_run` as soon as it fails, or [`Error::Timeout`] if the
+    /// hook was not removed in time.
+    pub fn wait_stopped(&self, timeout: Duration) -> Result<()> {
+        let progress = self.state.wait(timeout, |progress| {
+            progress.disabled || progress.status.is_some()
+        })?;
+
+        progress.status.map_or(Ok(()), Error::check)
+    }
+
+    /// Returns `true` if the hook thread has returned from `hook_run`.
+    #[must_use]
+    pub fn is_finished(&self) -> bool {
+        self.thread.as_ref().map_or(true, JoinHandle::is_finished)
+    }
+
+    /// Returns the payload of the oldest dispatcher panic that was not taken yet.
+    ///
+    /// Panics are kept whatever the [`PanicPolicy`], so they can be reported or resumed with
+    /// [`std::panic::resume_unwind`].
+    #[must_use]
+    pub fn take_panic(&self) -> Option<Box<dyn Any + Send>> {
+        self.state.lock_panics().pop_front()
+    }
+
+    /// Stops the hook and waits for its thread to finish.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error reported by `hook_run`, if any.
+    ///
+    /// # Panics
+    ///
+    /// Resumes the oldest dispatcher panic not taken by [`HookHandle::take_panic`], if any.
+    pub fn stop(mut self) -> Result<()> {
+        let result = self.shutdown();
+
+        if let Some(payload) = self.take_panic() {
+            panic::resume_unwind(payload);
+        }
+
+        result
+    }
+
+    fn shutdown(&mut self) -> Result<()> {
+        let Some(thread) = self.thread.take() else {
+            return Ok(());
+        };
+
+        // Joining from within the dispatcher would deadlock, so let the hook thread release the
+        // hook once `hook_run` returns instead.
+        if thread.thread().id() == thread::current().id() {
+            DETACHED.store(true, Ordering::Release);
+            // SAFETY: the hook is running on this very thread.
+            return Error::check(unsafe { sys::hook_stop() });
+        }
+
+        // `hook_stop` fails until `hook_run` has finished installing the hook, so keep retrying
+        // until it either succeeds or `hook_run` returns on its own.
+        // SAFETY: `hook_stop` is safe to call from any thread.
+        while !thread.is_finished() && unsafe { sys::hook_stop() } != sys::UIOHOOK_SUCCESS {
+            thread::sleep(STOP_RETRY_INTERVAL);
+        }
+
+        let status = thread.join();
+        release();
+
+        match status {
+            Ok(status) => Error::check(status),
+            Err(payload) => panic::resume_unwind(payload),
+        }
+    }
+}
+
+impl Drop for HookHandle {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+/// How far the hook has come, as observed from the hook thread.
+#[derive(Clone, Copy, Debug, Default)]
+struct Progress {
+    /// Set by `EVENT_HOOK_ENABLED`.
+    enabled: bool,
+    /// Set by `EVENT_HOOK_DISABLED`.
+    disabled: bool,
+    /// Set once `hook_run` has returned.
+    status: Option<c_int>,
+}
+
+/// The [`Progress`] of a hook, shared between its thread and its handle.
+#[derive(Debug, Default)]
+struct State {
+    progress: Mutex<Progress>,
+    changed: Condvar,
+    /// Payloads of the dispatcher panics, oldest first.
+    panics: Mutex<VecDeque<Box<dyn Any + Send>>>,
+}
+
+impl State {
+    fn lock_panics(&self) -> MutexGuard<'_, VecDeque<Box<dyn Any + Send>>> {
+        self.panics.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn panicked(&self, payload: Box<dyn Any + Send>) {
+        self.lock_panics().push_back(payload);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Progress> {
+        self.progress.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Progress)) {
+        f(&mut self.lock());
+        self.changed.notify_all();
+    }
+
+    fn observe(&self, event: &sys::uiohook_event) {
+        match event.r#type {
+            sys::event_type::EVENT_HOOK_ENABLED => self.update(|progress| progress.enabled = true),
+            sys::event_type::EVENT_HOOK_DISABLED => {
+                self.update(|progress| progress.disabled = true);
+            }
+            _ => {}
+        }
+    }
+
+    /// Waits up to `timeout` for `done` to hold.
+    fn wait(&self, timeout: Duration, done: impl Fn(&Progress) -> bool) -> Result<Progress> {
+        let deadline = Instant::now().checked_add(timeout);
+        let mut progress = self.lock();
+
+        while !done(&progress) {
+            progress = match deadline {
+                Some(deadline) => {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    if remaining.is_zero() {
+                        return Err(Error::Timeout);
+                    }
+
+                    self.changed
+                        .wait_timeout(progress, remaining)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+                // Too far in the future to represent, which is as good as forever.
+                None => self
+                    .changed
+                    .wait(progress)
+                    .unwrap_or_else(PoisonError::into_inner),
+            };
+        }
+
+        Ok(*progress)
+    }
+}
+
+/// Allows a new hook to be spawned.
+fn release() {
+    ACTIVE.store(false, Ordering::Release);
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::sync::mpsc;
+
+    use super::*;
+    use crate::{builder::EventBuilder, error::X11Error, key::Key, sys::mock};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn dispatches_events_until_stopped() {
+        let _mock = mock::lock();
+        mock::script([EventBuilder::key_pressed(Key::A).build_raw()]);
+
+        let (sender, receiver) = mpsc::channel();
+        let handle = Hook::builder()
+            .on_event(move |event| sender.send(event).unwrap())
+            .spawn()
+            .unwrap();
+
+        handle.wait_ready(TIMEOUT).unwrap();
+        assert_eq!(
+            receiver.recv_timeout(TIMEOUT),
+            Ok(EventBuilder::hook_enabled().build())
+        );
+        assert_eq!(
+            receiver.recv_timeout(TIMEOUT),
+            Ok(EventBuilder::key_pressed(Key::A).build())
+        );
+
+        handle.stop().unwrap();
+        assert_eq!(
+            receiver.try_recv(),
+            Ok(EventBuilder::hook_disabled().build())
+        );
+        assert!(!mock::is_running());
+    }
+
+    #[test]
+    fn reports_run_errors() {
+        let _mock = mock::lock();
+        mock::fail_run(sys::UIOHOOK_ERROR_X_OPEN_DISPLAY);
+
+        let handle = Hook::builder().spawn().unwrap();
+
+        assert!(matches!(
+            handle.wait_ready(TIMEOUT),
+            Err(Error::X11(X11Error::OpenDisplay))
+        ));
+        assert!(matches!(
+            handle.stop(),
+            Err(Error::X11(X11Error::OpenDisplay))
+        ));
+    }
+
+    #[test]
+    fn runs_a_single_hook() {
+        let _mock = mock::lock();
+
+        let handle = Hook::builder().spawn().unwrap();
+        assert!(matches!(
+            Hook::builder().spawn(),
+            Err(Error::AlreadyRunning)
+        ));
+        handle.stop().unwrap();
+
+        Hook::builder().spawn().unwrap().stop().unwrap();
+    }
+
+    #[test]
+    fn stops_on_panic() {
+        let _mock = mock::lock();
+        mock::script([EventBuilder::key_pressed(Key::A).build_raw()]);
+
+        let handle = Hook::builder()
+            .on_event(|event| assert!(!matches!(event, Event::KeyPressed(_))))
+            .spawn()
+            .unwrap();
+
+        handle.wait_stopped(TIMEOUT).unwrap();
+        assert!(handle.take_panic().is_some());
+        handle.stop().unwrap();
+    }
+
+    #[cfg(feature = "v1_3")]
+    #[test]
+    fn consumes_intercepted_events() {
+        let _mock = mock::lock();
+        mock::set_stop_when_idle(true);
+        mock::script([
+            EventBuilder::key_pressed(Key::A).build_raw(),
+            EventBuilder::key_pressed(Key::B).build_raw(),
+        ]);
+
+        let handle = Hook::builder()
+            .intercept(|event| match event {
+                Event::KeyPressed(event) if event.key == Key::A => Delivery::Consume,
+                _ => Delivery::Forward,
+            })
+            .spawn()
+            .unwrap();
+
+        handle.wait_stopped(TIMEOUT).unwrap();
+        handle.stop().unwrap();
+
+        let consumed: Vec<_> = mock::take_consumed().iter().map(Event::try_from).collect();
+        assert_eq!(consumed, [Ok(EventBuilder::key_pressed(Key::A).build())]);
+    }
+}
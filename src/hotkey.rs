@@ -0,0 +1,804 @@
+//! Global hotkeys, such as `Ctrl+Alt+K`, on top of the keyboard events.
+
+use std::{
+    collections::HashSet,
+    error, fmt,
+    str::FromStr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use crate::{
+    event::{Event, KeyboardEvent},
+    hook::HookBuilder,
+    key::{Key, ParseKeyError},
+    modifiers::Modifiers,
+};
+
+type Callback = Box<dyn FnMut(&KeyboardEvent) + Send>;
+
+/// The modifier keys a hotkey can require, as `(name, left, right)`.
+const FAMILIES: [(&str, Modifiers, Modifiers); 4] = [
+    ("Ctrl", Modifiers::CTRL_LEFT, Modifiers::CTRL_RIGHT),
+    ("Alt", Modifiers::ALT_LEFT, Modifiers::ALT_RIGHT),
+    ("Shift", Modifiers::SHIFT_LEFT, Modifiers::SHIFT_RIGHT),
+    ("Meta", Modifiers::META_LEFT, Modifiers::META_RIGHT),
+];
+
+/// A key pressed while holding a combination of modifier keys.
+///
+/// Parses from accelerator strings such as `Ctrl+Alt+K`: modifiers joined by `+`, followed by a
+/// key name accepted by [`Key::from_str`], all ignoring ASCII case. The modifiers are `Ctrl` (or
+/// `Control`), `Alt` (or `Option`), `Shift` and `Meta` (or `Super`, `Win`, `Cmd`, `Command`); a
+/// `Left` or `Right` suffix, or an `L` or `R` prefix, selects one side, as in `CtrlLeft` or `RAlt`.
+/// Without one, either side is accepted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    /// The modifier keys to hold; both bits of a modifier accept either side.
+    pub modifiers: Modifiers,
+    /// The key to press.
+    pub key: Key,
+}
+
+impl Hotkey {
+    /// Creates a hotkey; only the modifier key bits of `modifiers` are kept.
+    #[must_use]
+    pub fn new(modifiers: Modifiers, key: Key) -> Self {
+        Self {
+            modifiers: modifiers.keys(),
+            key,
+        }
+    }
+
+    /// Returns `true` if pressing `key` while holding `mask` triggers the hotkey.
+    #[must_use]
+    pub fn matches(self, key: Key, mask: Modifiers, sides: SideMatching) -> bool {
+        // The mask of a modifier key press already includes the key itself.
+        let held = mask.keys().difference(Modifiers::of_key(key));
+
+        key == self.key
+            && FAMILIES.iter().all(|&(_, left, right)| {
+                accepted(self.modifiers, left, right, sides) & state(held, left, right) != 0
+            })
+    }
+
+    /// Returns the hotkey accepting either side of every modifier it requires.
+    pub(crate) fn either_side(self) -> Self {
+        let modifiers = FAMILIES
+            .iter()
+            .filter(|&&(_, left, right)| self.modifiers.intersects(left | right))
+            .fold(Modifiers::empty(), |modifiers, &(_, left, right)| {
+                modifiers | left | right
+            });
+
+        Self { modifiers, ..self }
+    }
+
+    /// Returns `true` if some combination of modifiers triggers both hotkeys.
+    pub(crate) fn overlaps(
+        self,
+        sides: SideMatching,
+        other: Self,
+        other_sides: SideMatching,
+    ) -> bool {
+        self.key == other.key
+            && FAMILIES.iter().all(|&(_, left, right)| {
+                accepted(self.modifiers, left, right, sides)
+                    & accepted(other.modifiers, left, right, other_sides)
+                    != 0
+            })
+    }
+}
+
+impl fmt::Display for Hotkey {
+    /// Writes the hotkey as an accelerator string that parses back to it, such as `Ctrl+Alt+K`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, left, right) in FAMILIES {
+            match (
+                self.modifiers.contains(left),
+                self.modifiers.contains(right),
+            ) {
+                (true, true) => write!(f, "{name}+")?,
+                (true, false) => write!(f, "{name}Left+")?,
+                (false, true) => write!(f, "{name}Right+")?,
+                (false, false) => {}
+            }
+        }
+
+        write!(f, "{}", self.key)
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = ParseHotkeyError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split('+').map(str::trim);
+        let key = parts.next_back().filter(|key| !key.is_empty());
+        let key = key.ok_or(ParseHotkeyError::MissingKey)?;
+
+        let mut modifiers = Modifiers::empty();
+        for name in parts {
+            modifiers |= parse_modifier(name)
+                .ok_or_else(|| ParseHotkeyError::UnknownModifier(name.to_owned()))?;
+        }
+
+        let key = key
+            .parse()
+            .map_err(|_: ParseKeyError| ParseHotkeyError::UnknownKey(key.to_owned()))?;
+
+        Ok(Self { modifiers, key })
+    }
+}
+
+fn parse_modifier(name: &str) -> Option<Modifiers> {
+    let lower = name.to_ascii_lowercase();
+
+    let (name, side) = if let Some(name) = lower.strip_suffix("left") {
+        (name, Some(true))
+    } else if let Some(name) = lower.strip_suffix("right") {
+        (name, Some(false))
+    } else {
+        (lower.as_str(), None)
+    };
+
+    let family = |name: &str| match name {
+        "ctrl" | "control" => Some(0),
+        "alt" | "option" => Some(1),
+        "shift" => Some(2),
+        "meta" | "super" | "win" | "cmd" | "command" => Some(3),
+        _ => None,
+    };
+
+    let (index, side) = match (family(name), side) {
+        (Some(index), side) => (index, side),
+        (None, None) => match name.split_at(name.len().min(1)) {
+            ("l", name) => (family(name)?, Some(true)),
+            ("r", name) => (family(name)?, Some(false)),
+            _ => return None,
+        },
+        (None, Some(_)) => return None,
+    };
+
+    let (_, left, right) = FAMILIES[index];
+    Some(match side {
+        Some(true) => left,
+        Some(false) => right,
+        None => left | right,
+    })
+}
+
+/// The error returned when parsing a [`Hotkey`] fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseHotkeyError {
+    /// The string does not end with a key.
+    MissingKey,
+    /// The key name is not known.
+    UnknownKey(String),
+    /// One of the modifier names is not known.
+    UnknownModifier(String),
+}
+
+impl fmt::Display for ParseHotkeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingKey => f.write_str("hotkey has no key"),
+            Self::UnknownKey(name) => write!(f, "unknown key name {name:?}"),
+            Self::UnknownModifier(name) => write!(f, "unknown modifier name {name:?}"),
+        }
+    }
+}
+
+impl error::Error for ParseHotkeyError {}
+
+/// How a hotkey treats the left and right modifier keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum SideMatching {
+    /// Either side satisfies a modifier, even one written as `CtrlLeft`.
+    #[default]
+    Insensitive,
+    /// A modifier written with a side requires exactly that side; one written without a side
+    /// still accepts either.
+    Sensitive,
+}
+
+/// Bits of the held states of a modifier family: neither side, left, right and both.
+const NEITHER: u8 = 1 << 0;
+const LEFT: u8 = 1 << 1;
+const RIGHT: u8 = 1 << 2;
+const BOTH: u8 = 1 << 3;
+
+/// Returns the state of a modifier family in `mask`, as one of the bits above.
+fn state(mask: Modifiers, left: Modifiers, right: Modifiers) -> u8 {
+    match (mask.contains(left), mask.contains(right)) {
+        (false, false) => NEITHER,
+        (true, false) => LEFT,
+        (false, true) => RIGHT,
+        (true, true) => BOTH,
+    }
+}
+
+/// Returns the states of a modifier family that satisfy `required`.
+fn accepted(required: Modifiers, left: Modifiers, right: Modifiers, sides: SideMatching) -> u8 {
+    match (state(required, left, right), sides) {
+        (NEITHER, _) => NEITHER,
+        (_, SideMatching::Insensitive) | (BOTH, SideMatching::Sensitive) => LEFT | RIGHT | BOTH,
+        (side, SideMatching::Sensitive) => side,
+    }
+}
+
+/// Identifies a hotkey registered with a [`HotkeyManager`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HotkeyId(u64);
+
+/// The error returned when a hotkey could be triggered by the same keys as a registered one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HotkeyConflict {
+    /// The hotkey that was not registered.
+    pub hotkey: Hotkey,
+    /// The registered hotkey it conflicts with.
+    pub existing: Hotkey,
+    /// The identifier of the registered hotkey.
+    pub id: HotkeyId,
+}
+
+impl fmt::Display for HotkeyConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hotkey {} conflicts with the registered hotkey {}",
+            self.hotkey, self.existing
+        )
+    }
+}
+
+impl error::Error for HotkeyConflict {}
+
+/// A registered hotkey.
+struct Registration {
+    id: HotkeyId,
+    hotkey: Hotkey,
+    sides: SideMatching,
+    repeat: bool,
+    on_press: Option<Mutex<Callback>>,
+    on_release: Option<Mutex<Callback>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: u64,
+    hotkeys: Vec<Arc<Registration>>,
+    /// Keys currently held, to tell auto-repeated presses apart.
+    pressed: HashSet<Key>,
+    /// Hotkeys whose key is still held since they were triggered, waiting for its release.
+    active: Vec<Arc<Registration>>,
+}
+
+/// Calls back when registered hotkeys are pressed and released.
+///
+/// Feed the manager with [`HotkeyManager::handle`], or register it with
+/// [`HookBuilder::on_hotkeys`]. Clones share the same hotkeys.
+///
+/// As with an [`EventBus`](crate::EventBus), the [`PanicPolicy`](crate::PanicPolicy) of the hook
+/// applies to the manager as a whole: with `DropHandler`, a panicking callback disables every
+/// hotkey.
+///
+/// ```no_run
+/// use uiohook::{Hook, HotkeyManager};
+///
+/// let hotkeys = HotkeyManager::new();
+/// hotkeys
+///     .hotkey("Ctrl+Alt+K".parse()?)
+///     .on_press(|_| println!("pressed"))
+///     .register()?;
+///
+/// let handle = Hook::builder().on_hotkeys(&hotkeys).spawn()?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct HotkeyManager {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl HotkeyManager {
+    /// Creates a manager without hotkeys.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a builder to configure the callbacks of `hotkey`.
+    pub fn hotkey(&self, hotkey: Hotkey) -> HotkeyBuilder<'_> {
+        HotkeyBuilder {
+            manager: self,
+            hotkey,
+            sides: SideMatching::default(),
+            repeat: false,
+            on_press: None,
+            on_release: None,
+        }
+    }
+
+    /// Removes a hotkey, returning `false` if it was not registered.
+    ///
+    /// A release callback is not called if the key of the hotkey is still held.
+    // Mostly called for its effect, like `HashSet::remove`.
+    #[allow(clippy::must_use_candidate)]
+    pub fn unregister(&self, id: HotkeyId) -> bool {
+        let mut inner = self.lock();
+        inner.active.retain(|registration| registration.id != id);
+
+        let len = inner.hotkeys.len();
+        inner.hotkeys.retain(|registration| registration.id != id);
+        inner.hotkeys.len() != len
+    }
+
+    /// Returns the registered hotkeys, in registration order.
+    #[must_use]
+    pub fn hotkeys(&self) -> Vec<(HotkeyId, Hotkey)> {
+        self.lock()
+            .hotkeys
+            .iter()
+            .map(|registration| (registration.id, registration.hotkey))
+            .collect()
+    }
+
+    /// Calls the callbacks of the hotkeys triggered by `event`, returning `true` if there were any.
+    ///
+    /// A press triggers the hotkeys whose key it is and whose modifiers are held, ignoring lock
+    /// keys and mouse buttons; any other modifier held prevents the match. Presses repeated while
+    /// the key is held only trigger hotkeys registered with [`HotkeyBuilder::repeat`]. Releasing
+    /// the key calls the release callbacks of the hotkeys it triggered, whatever the modifiers
+    /// held by then.
+    ///
+    /// Callbacks may register and unregister hotkeys without deadlocking.
+    pub fn handle(&self, event: &Event) -> bool {
+        let (event, triggered, pressed) = match event {
+            Event::KeyPressed(event) => (event, self.press(event), true),
+            Event::KeyReleased(event) => (event, self.release(event), false),
+            Event::HookEnabled(_) | Event::HookDisabled(_) => {
+                let mut inner = self.lock();
+                inner.pressed.clear();
+                inner.active.clear();
+                return false;
+            }
+            _ => return false,
+        };
+
+        for registration in &triggered {
+            let callback = if pressed {
+                &registration.on_press
+            } else {
+                &registration.on_release
+            };
+
+            if let Some(callback) = callback {
+                (callback.lock().unwrap_or_else(PoisonError::into_inner))(event);
+            }
+        }
+
+        !triggered.is_empty()
+    }
+
+    fn press(&self, event: &KeyboardEvent) -> Vec<Arc<Registration>> {
+        let mut inner = self.lock();
+        let repeated = !inner.pressed.insert(event.key);
+
+        let triggered: Vec<_> = inner
+            .hotkeys
+            .iter()
+            .filter(|registration| !repeated || registration.repeat)
+            .filter(|registration| {
+                registration
+                    .hotkey
+                    .matches(event.key, event.mask, registration.sides)
+            })
+            .cloned()
+            .collect();
+
+        if !repeated {
+            inner.active.extend(triggered.iter().cloned());
+        }
+
+        triggered
+    }
+
+    fn release(&self, event: &KeyboardEvent) -> Vec<Arc<Registration>> {
+        let mut inner = self.lock();
+        inner.pressed.remove(&event.key);
+
+        let (released, active) = inner
+            .active
+            .drain(..)
+            .partition(|registration| registration.hotkey.key == event.key);
+        inner.active = active;
+
+        released
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for HotkeyManager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotkeyManager")
+            .field("hotkeys", &self.hotkeys())
+            .finish()
+    }
+}
+
+impl HookBuilder {
+    /// Triggers the hotkeys of `manager` with the events produced by the hook.
+    ///
+    /// Replaces any dispatcher set on the builder.
+    pub fn on_hotkeys(self, manager: &HotkeyManager) -> Self {
+        let manager = manager.clone();
+        self.on_event(move |event| {
+            manager.handle(&event);
+        })
+    }
+}
+
+/// Configures a hotkey before registering it with [`HotkeyBuilder::register`].
+#[must_use]
+pub struct HotkeyBuilder<'a> {
+    manager: &'a HotkeyManager,
+    hotkey: Hotkey,
+    sides: SideMatching,
+    repeat: bool,
+    on_press: Option<Callback>,
+    on_release: Option<Callback>,
+}
+
+impl HotkeyBuilder<'_> {
+    /// Sets how the left and right modifier keys are matched.
+    ///
+    /// Defaults to [`SideMatching::Insensitive`].
+    pub fn sides(mut self, sides: SideMatching) -> Self {
+        self.sides = sides;
+        self
+    }
+
+    /// Also triggers the hotkey for the presses repeated while its key is held.
+    ///
+    /// Defaults to `false`.
+    pub fn repeat(mut self, repeat: bool) -> Self {
+        self.repeat = repeat;
+        self
+    }
+
+    /// Calls `callback` with the key press that triggered the hotkey.
+    ///
+    /// The callback is called from the thread feeding the manager, usually the hook thread, so it
+    /// should return quickly.
+    pub fn on_press<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&KeyboardEvent) + Send + 'static,
+    {
+        self.on_press = Some(Box::new(callback));
+        self
+    }
+
+    /// Calls `callback` with the release of the key that triggered the hotkey.
+    pub fn on_release<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&KeyboardEvent) + Send + 'static,
+    {
+        self.on_release = Some(Box::new(callback));
+        self
+    }
+
+    /// Registers the hotkey with the manager.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`HotkeyConflict`] if a registered hotkey would be triggered by some of the same
+    /// key presses, taking the side matching of both into account: `Ctrl+K` conflicts with
+    /// `CtrlLeft+K`, while `CtrlLeft+K` and `CtrlRight+K` only conflict if either of them is side
+    /// insensitive.
+    pub fn register(self) -> Result<HotkeyId, HotkeyConflict> {
+        let mut inner = self.manager.lock();
+
+        if let Some(existing) = inner.hotkeys.iter().find(|registration| {
+            registration
+                .hotkey
+                .overlaps(registration.sides, self.hotkey, self.sides)
+        }) {
+            return Err(HotkeyConflict {
+                hotkey: self.hotkey,
+                existing: existing.hotkey,
+                id: existing.id,
+            });
+        }
+
+        let id = HotkeyId(inner.next_id);
+        inner.next_id += 1;
+        inner.hotkeys.push(Arc::new(Registration {
+            id,
+            hotkey: self.hotkey,
+            sides: self.sides,
+            repeat: self.repeat,
+            on_press: self.on_press.map(Mutex::new),
+            on_release: self.on_release.map(Mutex::new),
+        }));
+
+        Ok(id)
+    }
+}
+
+impl fmt::Debug for HotkeyBuilder<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HotkeyBuilder")
+            .field("hotkey", &self.hotkey)
+            .field("sides", &self.sides)
+            .field("repeat", &self.repeat)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::builder::EventBuilder;
+
+    fn hotkey(s: &str) -> Hotkey {
+        s.parse().unwrap()
+    }
+
+    fn press(manager: &HotkeyManager, key: Key, mask: Modifiers) -> bool {
+        manager.handle(&EventBuilder::key_pressed(key).mask(mask).build())
+    }
+
+    fn release(manager: &HotkeyManager, key: Key, mask: Modifiers) -> bool {
+        manager.handle(&EventBuilder::key_released(key).mask(mask).build())
+    }
+
+    /// Registers `hotkey` with callbacks counting its presses and releases.
+    fn counted(builder: HotkeyBuilder<'_>) -> (HotkeyId, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let (presses, releases) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+        let id = builder
+            .on_press({
+                let presses = Arc::clone(&presses);
+                move |_| {
+                    presses.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .on_release({
+                let releases = Arc::clone(&releases);
+                move |_| {
+                    releases.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .register()
+            .unwrap();
+
+        (id, presses, releases)
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        assert_eq!(
+            hotkey("Ctrl+Alt+K"),
+            Hotkey::new(Modifiers::CTRL | Modifiers::ALT, Key::K)
+        );
+        assert_eq!(hotkey("F1"), Hotkey::new(Modifiers::empty(), Key::F1));
+        assert_eq!(
+            hotkey("control+option+shift+super+space"),
+            Hotkey::new(
+                Modifiers::CTRL | Modifiers::ALT | Modifiers::SHIFT | Modifiers::META,
+                Key::Space
+            )
+        );
+
+        for meta in ["Meta", "Super", "Win", "Cmd", "Command"] {
+            assert_eq!(
+                hotkey(&format!("{meta}+K")),
+                Hotkey::new(Modifiers::META, Key::K),
+                "{meta}"
+            );
+        }
+    }
+
+    #[test]
+    fn parses_sides() {
+        assert_eq!(hotkey("CtrlLeft+K").modifiers, Modifiers::CTRL_LEFT);
+        assert_eq!(hotkey("ShiftRight+K").modifiers, Modifiers::SHIFT_RIGHT);
+        assert_eq!(hotkey("LAlt+K").modifiers, Modifiers::ALT_LEFT);
+        assert_eq!(hotkey("rcmd+K").modifiers, Modifiers::META_RIGHT);
+        assert_eq!(
+            hotkey("CtrlLeft+CtrlRight+K").modifiers,
+            Modifiers::CTRL_LEFT | Modifiers::CTRL_RIGHT
+        );
+    }
+
+    #[test]
+    fn ignores_case_and_spaces() {
+        let expected = Hotkey::new(Modifiers::CTRL | Modifiers::SHIFT, Key::Equals);
+
+        assert_eq!(hotkey("ctrl+shift+equals"), expected);
+        assert_eq!(hotkey("CTRL+SHIFT+EQUALS"), expected);
+        assert_eq!(hotkey(" Ctrl + Shift + Equals "), expected);
+    }
+
+    #[test]
+    fn rejects_invalid_hotkeys() {
+        for s in ["", "Ctrl+", "Ctrl++", "Ctrl+ "] {
+            assert_eq!(
+                s.parse::<Hotkey>(),
+                Err(ParseHotkeyError::MissingKey),
+                "{s:?}"
+            );
+        }
+
+        assert_eq!(
+            "Ctrl+Nope".parse::<Hotkey>(),
+            Err(ParseHotkeyError::UnknownKey("Nope".to_owned()))
+        );
+
+        for modifier in ["Hyper", "CtrlUp", "XCtrl", "LeftCtrl", "Left", ""] {
+            assert_eq!(
+                format!("{modifier}+K").parse::<Hotkey>(),
+                Err(ParseHotkeyError::UnknownModifier(modifier.to_owned())),
+                "{modifier:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn displays_what_it_parses() {
+        for (s, displayed) in [
+            ("Ctrl+Alt+K", "Ctrl+Alt+K"),
+            ("shift+control+k", "Ctrl+Shift+K"),
+            ("RCtrl+LMeta+F1", "CtrlRight+MetaLeft+F1"),
+            ("Space", "Space"),
+        ] {
+            let hotkey = hotkey(s);
+            assert_eq!(hotkey.to_string(), displayed);
+            assert_eq!(displayed.parse(), Ok(hotkey));
+        }
+    }
+
+    #[test]
+    fn matches_sides() {
+        let left = hotkey("CtrlLeft+K");
+        let either = hotkey("Ctrl+K");
+        let (insensitive, sensitive) = (SideMatching::Insensitive, SideMatching::Sensitive);
+
+        for mask in [
+            Modifiers::CTRL_LEFT,
+            Modifiers::CTRL_RIGHT,
+            Modifiers::CTRL_LEFT | Modifiers::CTRL_RIGHT,
+        ] {
+            assert!(left.matches(Key::K, mask, insensitive), "{mask:?}");
+            assert!(either.matches(Key::K, mask, sensitive), "{mask:?}");
+        }
+
+        assert!(left.matches(Key::K, Modifiers::CTRL_LEFT, sensitive));
+        assert!(!left.matches(Key::K, Modifiers::CTRL_RIGHT, sensitive));
+        assert!(!left.matches(Key::K, Modifiers::CTRL, sensitive));
+    }
+
+    #[test]
+    fn matches_exact_modifiers() {
+        let ctrl_k = hotkey("Ctrl+K");
+        let sides = SideMatching::Insensitive;
+
+        assert!(!ctrl_k.matches(Key::K, Modifiers::empty(), sides));
+        assert!(!ctrl_k.matches(Key::J, Modifiers::CTRL_LEFT, sides));
+        assert!(!ctrl_k.matches(Key::K, Modifiers::CTRL_LEFT | Modifiers::SHIFT_LEFT, sides));
+
+        // Lock keys and buttons are not modifiers.
+        let mask = Modifiers::CTRL_LEFT | Modifiers::CAPS_LOCK | Modifiers::BUTTON1;
+        assert!(ctrl_k.matches(Key::K, mask, sides));
+
+        // The mask of a modifier key press includes the key itself.
+        let ctrl_shift = hotkey("Ctrl+ShiftLeft");
+        let mask = Modifiers::CTRL_LEFT | Modifiers::SHIFT_LEFT;
+        assert!(ctrl_shift.matches(Key::ShiftLeft, mask, sides));
+    }
+
+    #[test]
+    fn calls_back_once_per_press() {
+        let manager = HotkeyManager::new();
+        let (_, presses, releases) = counted(manager.hotkey(hotkey("Ctrl+K")));
+
+        assert!(press(&manager, Key::K, Modifiers::CTRL_LEFT));
+        // Repeated while held.
+        assert!(!press(&manager, Key::K, Modifiers::CTRL_LEFT));
+        assert!(!press(&manager, Key::K, Modifiers::CTRL_LEFT));
+        // Released once Ctrl is already up.
+        assert!(release(&manager, Key::K, Modifiers::empty()));
+
+        assert!(press(&manager, Key::K, Modifiers::CTRL_RIGHT));
+        assert!(release(&manager, Key::K, Modifiers::CTRL_RIGHT));
+        assert!(!press(&manager, Key::K, Modifiers::empty()));
+
+        assert_eq!(presses.load(Ordering::Relaxed), 2);
+        assert_eq!(releases.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn repeats_if_asked() {
+        let manager = HotkeyManager::new();
+        let (_, presses, releases) = counted(manager.hotkey(hotkey("Ctrl+K")).repeat(true));
+
+        for _ in 0..3 {
+            assert!(press(&manager, Key::K, Modifiers::CTRL_LEFT));
+        }
+        assert!(release(&manager, Key::K, Modifiers::CTRL_LEFT));
+
+        assert_eq!(presses.load(Ordering::Relaxed), 3);
+        assert_eq!(releases.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn forgets_held_keys_when_the_hook_restarts() {
+        let manager = HotkeyManager::new();
+        let (_, presses, releases) = counted(manager.hotkey(hotkey("K")));
+
+        assert!(press(&manager, Key::K, Modifiers::empty()));
+        manager.handle(&EventBuilder::hook_disabled().build());
+        manager.handle(&EventBuilder::hook_enabled().build());
+        assert!(press(&manager, Key::K, Modifiers::empty()));
+
+        assert_eq!(presses.load(Ordering::Relaxed), 2);
+        assert!(release(&manager, Key::K, Modifiers::empty()));
+        assert_eq!(releases.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn detects_conflicts() {
+        let manager = HotkeyManager::new();
+        let id = manager.hotkey(hotkey("Ctrl+K")).register().unwrap();
+
+        for s in ["Ctrl+K", "CtrlLeft+K", "CtrlRight+K"] {
+            assert_eq!(
+                manager.hotkey(hotkey(s)).register(),
+                Err(HotkeyConflict {
+                    hotkey: hotkey(s),
+                    existing: hotkey("Ctrl+K"),
+                    id,
+                }),
+                "{s}"
+            );
+        }
+
+        manager.hotkey(hotkey("Ctrl+Shift+K")).register().unwrap();
+        manager.hotkey(hotkey("Alt+K")).register().unwrap();
+        manager.hotkey(hotkey("K")).register().unwrap();
+
+        assert!(manager.unregister(id));
+        assert!(!manager.unregister(id));
+        manager.hotkey(hotkey("CtrlLeft+K")).register().unwrap();
+    }
+
+    #[test]
+    fn detects_conflicts_between_sides() {
+        let manager = HotkeyManager::new();
+        let sensitive = SideMatching::Sensitive;
+
+        manager
+            .hotkey(hotkey("CtrlLeft+K"))
+            .sides(sensitive)
+            .register()
+            .unwrap();
+        manager
+            .hotkey(hotkey("CtrlRight+K"))
+            .sides(sensitive)
+            .register()
+            .unwrap();
+
+        // Pressing both sides at once triggers neither, so only side insensitive hotkeys conflict.
+        assert!(manager
+            .hotkey(hotkey("Ctrl+K"))
+            .sides(sensitive)
+            .register()
+            .is_err());
+        assert!(manager.hotkey(hotkey("CtrlLeft+K")).register().is_err());
+        assert_eq!(manager.hotkeys().len(), 2);
+    }
+}
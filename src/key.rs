@@ -0,0 +1,361 @@
+use std::{error, fmt, str::FromStr};
+
+use crate::sys;
+
+macro_rules! keys {
+    ($($variant:ident = $code:ident => $name:literal,)*) => {
+        /// A virtual key, one of the `VC_*` constants.
+        ///
+        /// Converting from a `u16` never fails: codes without a named variant are kept as
+        /// [`Key::Unknown`], so `Key::from(code).code() == code` holds for every code.
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[non_exhaustive]
+        pub enum Key {
+            $(
+                #[doc = concat!("`", stringify!($code), "`.")]
+                $variant,
+            )*
+            /// A key code without a named variant.
+            Unknown(u16),
+        }
+
+        impl Key {
+            const ALL: &'static [Self] = &[$(Self::$variant,)*];
+
+            /// Converts a `VC_*` key code into a key.
+            #[must_use]
+            pub const fn from_code(code: u16) -> Self {
+                match code {
+                    $(sys::$code => Self::$variant,)*
+                    code => Self::Unknown(code),
+                }
+            }
+
+            /// Returns the `VC_*` key code of the key.
+            #[must_use]
+            pub const fn code(self) -> u16 {
+                match self {
+                    $(Self::$variant => sys::$code,)*
+                    Self::Unknown(code) => code,
+                }
+            }
+
+            /// Returns the canonical name of the key, or `None` for [`Key::Unknown`].
+            #[must_use]
+            pub const fn name(self) -> Option<&'static str> {
+                match self {
+                    $(Self::$variant => Some($name),)*
+                    Self::Unknown(_) => None,
+                }
+            }
+        }
+    };
+}
+
+keys! {
+    Escape = VC_ESCAPE => "Escape",
+
+    F1 = VC_F1 => "F1",
+    F2 = VC_F2 => "F2",
+    F3 = VC_F3 => "F3",
+    F4 = VC_F4 => "F4",
+    F5 = VC_F5 => "F5",
+    F6 = VC_F6 => "F6",
+    F7 = VC_F7 => "F7",
+    F8 = VC_F8 => "F8",
+    F9 = VC_F9 => "F9",
+    F10 = VC_F10 => "F10",
+    F11 = VC_F11 => "F11",
+    F12 = VC_F12 => "F12",
+    F13 = VC_F13 => "F13",
+    F14 = VC_F14 => "F14",
+    F15 = VC_F15 => "F15",
+    F16 = VC_F16 => "F16",
+    F17 = VC_F17 => "F17",
+    F18 = VC_F18 => "F18",
+    F19 = VC_F19 => "F19",
+    F20 = VC_F20 => "F20",
+    F21 = VC_F21 => "F21",
+    F22 = VC_F22 => "F22",
+    F23 = VC_F23 => "F23",
+    F24 = VC_F24 => "F24",
+
+    Backquote = VC_BACKQUOTE => "Backquote",
+    Num1 = VC_1 => "1",
+    Num2 = VC_2 => "2",
+    Num3 = VC_3 => "3",
+    Num4 = VC_4 => "4",
+    Num5 = VC_5 => "5",
+    Num6 = VC_6 => "6",
+    Num7 = VC_7 => "7",
+    Num8 = VC_8 => "8",
+    Num9 = VC_9 => "9",
+    Num0 = VC_0 => "0",
+    Minus = VC_MINUS => "Minus",
+    Equals = VC_EQUALS => "Equals",
+    Backspace = VC_BACKSPACE => "Backspace",
+    Tab = VC_TAB => "Tab",
+    CapsLock = VC_CAPS_LOCK => "CapsLock",
+    A = VC_A => "A",
+    B = VC_B => "B",
+    C = VC_C => "C",
+    D = VC_D => "D",
+    E = VC_E => "E",
+    F = VC_F => "F",
+    G = VC_G => "G",
+    H = VC_H => "H",
+    I = VC_I => "I",
+    J = VC_J => "J",
+    K = VC_K => "K",
+    L = VC_L => "L",
+    M = VC_M => "M",
+    N = VC_N => "N",
+    O = VC_O => "O",
+    P = VC_P => "P",
+    Q = VC_Q => "Q",
+    R = VC_R => "R",
+    S = VC_S => "S",
+    T = VC_T => "T",
+    U = VC_U => "U",
+    V = VC_V => "V",
+    W = VC_W => "W",
+    X = VC_X => "X",
+    Y = VC_Y => "Y",
+    Z = VC_Z => "Z",
+    OpenBracket = VC_OPEN_BRACKET => "OpenBracket",
+    CloseBracket = VC_CLOSE_BRACKET => "CloseBracket",
+    BackSlash = VC_BACK_SLASH => "BackSlash",
+    Semicolon = VC_SEMICOLON => "Semicolon",
+    Quote = VC_QUOTE => "Quote",
+    Enter = VC_ENTER => "Enter",
+    Comma = VC_COMMA => "Comma",
+    Period = VC_PERIOD => "Period",
+    Slash = VC_SLASH => "Slash",
+    Space = VC_SPACE => "Space",
+
+    PrintScreen = VC_PRINTSCREEN => "PrintScreen",
+    ScrollLock = VC_SCROLL_LOCK => "ScrollLock",
+    Pause = VC_PAUSE => "Pause",
+    LesserGreater = VC_LESSER_GREATER => "LesserGreater",
+
+    Insert = VC_INSERT => "Insert",
+    Delete = VC_DELETE => "Delete",
+    Home = VC_HOME => "Home",
+    End = VC_END => "End",
+    PageUp = VC_PAGE_UP => "PageUp",
+    PageDown = VC_PAGE_DOWN => "PageDown",
+
+    Up = VC_UP => "Up",
+    Left = VC_LEFT => "Left",
+    Clear = VC_CLEAR => "Clear",
+    Right = VC_RIGHT => "Right",
+    Down = VC_DOWN => "Down",
+
+    NumLock = VC_NUM_LOCK => "NumLock",
+    KpDivide = VC_KP_DIVIDE => "KpDivide",
+    KpMultiply = VC_KP_MULTIPLY => "KpMultiply",
+    KpSubtract = VC_KP_SUBTRACT => "KpSubtract",
+    KpEquals = VC_KP_EQUALS => "KpEquals",
+    KpAdd = VC_KP_ADD => "KpAdd",
+    KpEnter = VC_KP_ENTER => "KpEnter",
+    KpSeparator = VC_KP_SEPARATOR => "KpSeparator",
+    Kp1 = VC_KP_1 => "Kp1",
+    Kp2 = VC_KP_2 => "Kp2",
+    Kp3 = VC_KP_3 => "Kp3",
+    Kp4 = VC_KP_4 => "Kp4",
+    Kp5 = VC_KP_5 => "Kp5",
+    Kp6 = VC_KP_6 => "Kp6",
+    Kp7 = VC_KP_7 => "Kp7",
+    Kp8 = VC_KP_8 => "Kp8",
+    Kp9 = VC_KP_9 => "Kp9",
+    Kp0 = VC_KP_0 => "Kp0",
+    KpEnd = VC_KP_END => "KpEnd",
+    KpDown = VC_KP_DOWN => "KpDown",
+    KpPageDown = VC_KP_PAGE_DOWN => "KpPageDown",
+    KpLeft = VC_KP_LEFT => "KpLeft",
+    KpClear = VC_KP_CLEAR => "KpClear",
+    KpRight = VC_KP_RIGHT => "KpRight",
+    KpHome = VC_KP_HOME => "KpHome",
+    KpUp = VC_KP_UP => "KpUp",
+    KpPageUp = VC_KP_PAGE_UP => "KpPageUp",
+    KpInsert = VC_KP_INSERT => "KpInsert",
+    KpDelete = VC_KP_DELETE => "KpDelete",
+
+    ShiftLeft = VC_SHIFT_L => "ShiftLeft",
+    ShiftRight = VC_SHIFT_R => "ShiftRight",
+    ControlLeft = VC_CONTROL_L => "ControlLeft",
+    ControlRight = VC_CONTROL_R => "ControlRight",
+    AltLeft = VC_ALT_L => "AltLeft",
+    AltRight = VC_ALT_R => "AltRight",
+    MetaLeft = VC_META_L => "MetaLeft",
+    MetaRight = VC_META_R => "MetaRight",
+    ContextMenu = VC_CONTEXT_MENU => "ContextMenu",
+
+    Power = VC_POWER => "Power",
+    Sleep = VC_SLEEP => "Sleep",
+    Wake = VC_WAKE => "Wake",
+    MediaPlay = VC_MEDIA_PLAY => "MediaPlay",
+    MediaStop = VC_MEDIA_STOP => "MediaStop",
+    MediaPrevious = VC_MEDIA_PREVIOUS => "MediaPrevious",
+    MediaNext = VC_MEDIA_NEXT => "MediaNext",
+    MediaSelect = VC_MEDIA_SELECT => "MediaSelect",
+    MediaEject = VC_MEDIA_EJECT => "MediaEject",
+    VolumeMute = VC_VOLUME_MUTE => "VolumeMute",
+    VolumeUp = VC_VOLUME_UP => "VolumeUp",
+    VolumeDown = VC_VOLUME_DOWN => "VolumeDown",
+    AppMail = VC_APP_MAIL => "AppMail",
+    AppCalculator = VC_APP_CALCULATOR => "AppCalculator",
+    AppMusic = VC_APP_MUSIC => "AppMusic",
+    AppPictures = VC_APP_PICTURES => "AppPictures",
+    BrowserSearch = VC_BROWSER_SEARCH => "BrowserSearch",
+    BrowserHome = VC_BROWSER_HOME => "BrowserHome",
+    BrowserBack = VC_BROWSER_BACK => "BrowserBack",
+    BrowserForward = VC_BROWSER_FORWARD => "BrowserForward",
+    BrowserStop = VC_BROWSER_STOP => "BrowserStop",
+    BrowserRefresh = VC_BROWSER_REFRESH => "BrowserRefresh",
+    BrowserFavorites = VC_BROWSER_FAVORITES => "BrowserFavorites",
+
+    Katakana = VC_KATAKANA => "Katakana",
+    Underscore = VC_UNDERSCORE => "Underscore",
+    Furigana = VC_FURIGANA => "Furigana",
+    Kanji = VC_KANJI => "Kanji",
+    Hiragana = VC_HIRAGANA => "Hiragana",
+    Yen = VC_YEN => "Yen",
+    KpComma = VC_KP_COMMA => "KpComma",
+
+    SunHelp = VC_SUN_HELP => "SunHelp",
+    SunStop = VC_SUN_STOP => "SunStop",
+    SunProps = VC_SUN_PROPS => "SunProps",
+    SunFront = VC_SUN_FRONT => "SunFront",
+    SunOpen = VC_SUN_OPEN => "SunOpen",
+    SunFind = VC_SUN_FIND => "SunFind",
+    SunAgain = VC_SUN_AGAIN => "SunAgain",
+    SunUndo = VC_SUN_UNDO => "SunUndo",
+    SunCopy = VC_SUN_COPY => "SunCopy",
+    SunInsert = VC_SUN_INSERT => "SunInsert",
+    SunCut = VC_SUN_CUT => "SunCut",
+
+    Undefined = VC_UNDEFINED => "Undefined",
+}
+
+impl Key {
+    /// Returns an iterator over every named key, in declaration order.
+    ///
+    /// [`Key::Unknown`] is never yielded.
+    #[must_use]
+    pub fn all() -> impl ExactSizeIterator<Item = Self> + Clone {
+        Self::ALL.iter().copied()
+    }
+}
+
+impl From<u16> for Key {
+    #[inline]
+    fn from(code: u16) -> Self {
+        Self::from_code(code)
+    }
+}
+
+impl From<Key> for u16 {
+    #[inline]
+    fn from(key: Key) -> Self {
+        key.code()
+    }
+}
+
+impl fmt::Display for Key {
+    /// Writes the canonical name of the key, or its code in hexadecimal for [`Key::Unknown`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "{:#06x}", self.code()),
+        }
+    }
+}
+
+impl FromStr for Key {
+    type Err = ParseKeyError;
+
+    /// Parses a canonical key name, ignoring ASCII case, or a hexadecimal key code such as
+    /// `0x0e5b`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(key) =
+            Self::all().find(|key| key.name().is_some_and(|name| name.eq_ignore_ascii_case(s)))
+        {
+            return Ok(key);
+        }
+
+        s.strip_prefix("0x")
+            .or_else(|| s.strip_prefix("0X"))
+            .and_then(|hex| u16::from_str_radix(hex, 16).ok())
+            .map(Self::from_code)
+            .ok_or(ParseKeyError(()))
+    }
+}
+
+/// The error returned when parsing a [`Key`] fails.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseKeyError(());
+
+impl fmt::Display for ParseKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unknown key name")
+    }
+}
+
+impl error::Error for ParseKeyError {}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn converts_every_code() {
+        for code in 0..=u16::MAX {
+            let key = Key::from(code);
+            assert_eq!(u16::from(key), code);
+            assert_eq!(key.name().is_none(), matches!(key, Key::Unknown(_)));
+        }
+    }
+
+    #[test]
+    fn lists_every_named_key() {
+        let keys: Vec<_> = Key::all().collect();
+        assert_eq!(keys.len(), Key::all().len());
+        assert_eq!(keys.first(), Some(&Key::Escape));
+        assert_eq!(keys.last(), Some(&Key::Undefined));
+
+        let codes: HashSet<_> = keys.iter().map(|key| key.code()).collect();
+        let names: HashSet<_> = keys.iter().map(|key| key.name().unwrap()).collect();
+        assert_eq!(codes.len(), keys.len());
+        assert_eq!(names.len(), keys.len());
+
+        for key in keys {
+            assert_eq!(Key::from_code(key.code()), key);
+        }
+    }
+
+    #[test]
+    fn parses_what_it_displays() {
+        for key in Key::all().chain([Key::Unknown(0xfff0)]) {
+            let name = key.to_string();
+            assert_eq!(name.parse(), Ok(key), "{name}");
+        }
+
+        assert_eq!(Key::Unknown(0xfff0).to_string(), "0xfff0");
+        assert_eq!(Key::PageUp.to_string(), "PageUp");
+    }
+
+    #[test]
+    fn parses_names_and_codes() {
+        assert_eq!("pageup".parse(), Ok(Key::PageUp));
+        assert_eq!("KPADD".parse(), Ok(Key::KpAdd));
+        assert_eq!(format!("{:#06x}", sys::VC_A).parse(), Ok(Key::A));
+        assert_eq!("0X1E".parse(), Ok(Key::from_code(0x1e)));
+
+        for s in ["", "Nope", "Page Up", "0x", "0xZZ", "0x10000", "1e"] {
+            assert_eq!(s.parse::<Key>(), Err(ParseKeyError(())), "{s:?}");
+        }
+    }
+}
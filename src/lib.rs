@@ -1,9 +1,9 @@
 #![warn(rust_2018_idioms, clippy::pedantic)]
 
 //! uiohook provides cross-platform keyboard and mouse event hooks from userland.
+// The examples of the README need the mock to run without a display server.
+#![cfg_attr(all(doctest, feature = "mock"), doc = include_str!("../README.md"))]
 
-// TODO(Unavailable): High level API.
-//
 // TODO(Unavailable): Alternative crate names:
 //
 // - easyhook
@@ -12,3 +12,50 @@
 // - hookrs
 //
 // DOCS(Unavailable): Available targets and OSes.
+
+mod builder;
+mod bus;
+mod channel;
+mod error;
+mod event;
+mod hook;
+mod hotkey;
+mod key;
+mod logger;
+mod modifiers;
+mod post;
+mod screen;
+mod sequence;
+mod settings;
+#[cfg(feature = "async")]
+mod stream;
+
+pub use builder::EventBuilder;
+pub use bus::{EventBus, Propagation, SubscriberBuilder, Subscription};
+pub use channel::{EventReceiver, DEFAULT_CHANNEL_CAPACITY};
+pub use error::{DarwinError, Error, Result, WindowsError, X11Error};
+pub use event::{
+    Event, EventKind, HookEvent, InvalidEvent, KeyboardEvent, MouseButton, MouseEvent, ScrollKind,
+    WheelDirection, WheelEvent,
+};
+#[cfg(feature = "v1_3")]
+pub use hook::Delivery;
+pub use hook::{Hook, HookBuilder, HookHandle, PanicPolicy};
+pub use hotkey::{
+    Hotkey, HotkeyBuilder, HotkeyConflict, HotkeyId, HotkeyManager, ParseHotkeyError, SideMatching,
+};
+pub use key::{Key, ParseKeyError};
+#[cfg(feature = "log")]
+pub use logger::set_log_logger;
+#[cfg(feature = "tracing")]
+pub use logger::set_tracing_logger;
+pub use logger::{set_logger, LogLevel};
+pub use modifiers::Modifiers;
+pub use post::{can_post, click, move_to, post, press, release, scroll, tap};
+pub use screen::{screens, Rect, Screen, VirtualDesktop};
+pub use sequence::{Sequence, SequenceConflict, SequenceMatcher, SequenceStep};
+pub use settings::{Setting, SettingChange, SystemSettings, Watch};
+#[cfg(feature = "async")]
+pub use stream::EventStream;
+
+pub use libuiohook_sys as sys;
@@ -0,0 +1,59 @@
+/*
+ * Formats libuiohook's printf-style log messages so they can be handed over to Rust, which cannot
+ * implement the variadic `logger_t` prototype itself.
+ */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+/* Implemented in `src/logger.rs`. */
+extern void uiohook_rs_log(unsigned int level, const char *message);
+
+static bool format_and_log(unsigned int level, const char *format, va_list args) {
+    char buffer[256];
+    char *message = buffer;
+
+    va_list retry_args;
+    va_copy(retry_args, args);
+
+    int length = vsnprintf(buffer, sizeof(buffer), format, args);
+
+    if (length >= 0 && (size_t) length >= sizeof(buffer)) {
+        message = malloc((size_t) length + 1);
+        if (message != NULL) {
+            vsnprintf(message, (size_t) length + 1, format, retry_args);
+        }
+    }
+    va_end(retry_args);
+
+    if (length < 0 || message == NULL) {
+        return false;
+    }
+
+    uiohook_rs_log(level, message);
+
+    if (message != buffer) {
+        free(message);
+    }
+
+    return true;
+}
+
+#ifdef UIOHOOK_RS_V1_3
+/* libuiohook 1.3 already hands over a `va_list`, along with user data we do not need. */
+void uiohook_rs_logger_proc(unsigned int level, void *user_data, const char *format, va_list args) {
+    (void) user_data;
+    format_and_log(level, format, args);
+}
+#else
+bool uiohook_rs_logger_proc(unsigned int level, const char *format, ...) {
+    va_list args;
+    va_start(args, format);
+    bool logged = format_and_log(level, format, args);
+    va_end(args);
+
+    return logged;
+}
+#endif
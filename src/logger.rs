@@ -0,0 +1,267 @@
+//! Routes libuiohook's diagnostics to a Rust callback.
+//!
+//! `logger_t` is variadic, so the message is formatted by a small C shim (`src/logger.c`) before
+//! it reaches Rust.
+
+use std::{
+    ffi::CStr,
+    fmt, mem,
+    os::raw::{c_char, c_uint},
+    panic::{self, AssertUnwindSafe},
+    sync::{PoisonError, RwLock},
+};
+
+use crate::{
+    error::{Error, Result},
+    sys::{self, log_level as ll},
+};
+
+type Logger = Box<dyn Fn(LogLevel, &str) + Send + Sync>;
+
+/// The type of `uiohook_rs_logger_proc`.
+#[cfg(not(feature = "v1_3"))]
+type LoggerProc = unsafe extern "C" fn(c_uint, *const c_char, ...) -> bool;
+#[cfg(feature = "v1_3")]
+type LoggerProc = unsafe extern "C" fn(c_uint, *mut std::ffi::c_void, *const c_char, sys::va_list);
+
+/// The callback `uiohook_rs_log` forwards messages to.
+static LOGGER: RwLock<Option<Logger>> = RwLock::new(None);
+
+#[cfg(not(feature = "v1_3"))]
+extern "C" {
+    fn uiohook_rs_logger_proc(level: c_uint, format: *const c_char, ...) -> bool;
+}
+
+#[cfg(feature = "v1_3")]
+extern "C" {
+    fn uiohook_rs_logger_proc(
+        level: c_uint,
+        user_data: *mut std::ffi::c_void,
+        format: *const c_char,
+        args: sys::va_list,
+    );
+}
+
+#[no_mangle]
+extern "C" fn uiohook_rs_log(level: c_uint, message: *const c_char) {
+    let Some(level) = LogLevel::from_raw(level) else {
+        return;
+    };
+
+    // SAFETY: the shim always passes a valid nul-terminated string.
+    let message = unsafe { CStr::from_ptr(message) }.to_string_lossy();
+
+    let logger = LOGGER.read().unwrap_or_else(PoisonError::into_inner);
+    if let Some(logger) = logger.as_ref() {
+        // Unwinding into C is undefined behaviour, and a failing logger is not worth aborting for.
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| logger(level, message.trim_end())));
+    }
+}
+
+/// The severity of a libuiohook log message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    /// `LOG_LEVEL_DEBUG`.
+    Debug,
+    /// `LOG_LEVEL_INFO`.
+    Info,
+    /// `LOG_LEVEL_WARN`.
+    Warn,
+    /// `LOG_LEVEL_ERROR`.
+    Error,
+}
+
+impl LogLevel {
+    /// Converts a raw `log_level`, returning `None` for unknown levels.
+    ///
+    /// The level is taken as the plain integer that [`sys::logger_t`] receives.
+    #[must_use]
+    pub fn from_raw(level: c_uint) -> Option<Self> {
+        [
+            (ll::LOG_LEVEL_DEBUG, Self::Debug),
+            (ll::LOG_LEVEL_INFO, Self::Info),
+            (ll::LOG_LEVEL_WARN, Self::Warn),
+            (ll::LOG_LEVEL_ERROR, Self::Error),
+        ]
+        .into_iter()
+        .find_map(|(raw, this)| (raw.0 == level).then_some(this))
+    }
+}
+
+impl From<LogLevel> for sys::log_level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => ll::LOG_LEVEL_DEBUG,
+            LogLevel::Info => ll::LOG_LEVEL_INFO,
+            LogLevel::Warn => ll::LOG_LEVEL_WARN,
+            LogLevel::Error => ll::LOG_LEVEL_ERROR,
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Debug => "DEBUG",
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Error => "ERROR",
+        })
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LogLevel> for log::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => Self::Debug,
+            LogLevel::Info => Self::Info,
+            LogLevel::Warn => Self::Warn,
+            LogLevel::Error => Self::Error,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<LogLevel> for tracing::Level {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Debug => Self::DEBUG,
+            LogLevel::Info => Self::INFO,
+            LogLevel::Warn => Self::WARN,
+            LogLevel::Error => Self::ERROR,
+        }
+    }
+}
+
+/// Sends every libuiohook log message to `logger`, replacing any previous logger.
+///
+/// Messages are formatted before `logger` is called, with trailing newlines removed.
+///
+/// # Errors
+///
+/// With the `dynamic` feature, returns `Error::Load` if libuiohook is not available; the previous
+/// logger is kept.
+pub fn set_logger<F>(logger: F) -> Result<()>
+where
+    F: Fn(LogLevel, &str) + Send + Sync + 'static,
+{
+    Error::ensure_loaded()?;
+
+    *LOGGER.write().unwrap_or_else(PoisonError::into_inner) = Some(Box::new(logger));
+
+    // SAFETY: the shim has the exact signature of `logger_t`; it is only declared `unsafe`
+    // because it is a foreign function.
+    let proc = unsafe { mem::transmute::<LoggerProc, sys::logger_t>(uiohook_rs_logger_proc) };
+
+    // SAFETY: `proc` is valid for the lifetime of the program.
+    #[cfg(not(feature = "v1_3"))]
+    unsafe {
+        sys::hook_set_logger_proc(proc);
+    }
+    // SAFETY: as above; the shim ignores its user data.
+    #[cfg(feature = "v1_3")]
+    unsafe {
+        sys::hook_set_logger_proc(proc, std::ptr::null_mut());
+    }
+
+    Ok(())
+}
+
+/// Sends every libuiohook log message to the [`log`] crate, under the `uiohook` target.
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_logger`].
+#[cfg(feature = "log")]
+pub fn set_log_logger() -> Result<()> {
+    set_logger(|level, message| log::log!(target: "uiohook", level.into(), "{message}"))
+}
+
+/// Sends every libuiohook log message to the [`tracing`] crate, under the `uiohook` target.
+///
+/// # Errors
+///
+/// Returns the same errors as [`set_logger`].
+#[cfg(feature = "tracing")]
+pub fn set_tracing_logger() -> Result<()> {
+    set_logger(|level, message| match level {
+        LogLevel::Debug => tracing::debug!(target: "uiohook", "{message}"),
+        LogLevel::Info => tracing::info!(target: "uiohook", "{message}"),
+        LogLevel::Warn => tracing::warn!(target: "uiohook", "{message}"),
+        LogLevel::Error => tracing::error!(target: "uiohook", "{message}"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_levels() {
+        let levels = [
+            (ll::LOG_LEVEL_DEBUG, LogLevel::Debug, "DEBUG"),
+            (ll::LOG_LEVEL_INFO, LogLevel::Info, "INFO"),
+            (ll::LOG_LEVEL_WARN, LogLevel::Warn, "WARN"),
+            (ll::LOG_LEVEL_ERROR, LogLevel::Error, "ERROR"),
+        ];
+
+        for (raw, level, name) in levels {
+            assert_eq!(LogLevel::from_raw(raw.0), Some(level));
+            assert_eq!(sys::log_level::from(level), raw);
+            assert_eq!(level.to_string(), name);
+        }
+
+        assert_eq!(LogLevel::from_raw(0), None);
+        assert_eq!(LogLevel::from_raw(5), None);
+        assert!(LogLevel::Debug < LogLevel::Error);
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn maps_levels_to_log() {
+        assert_eq!(log::Level::from(LogLevel::Warn), log::Level::Warn);
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn maps_levels_to_tracing() {
+        assert_eq!(tracing::Level::from(LogLevel::Warn), tracing::Level::WARN);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn formats_messages_through_the_shim() {
+        use std::{
+            ffi::CString,
+            sync::{Arc, Mutex},
+        };
+
+        use crate::sys::mock;
+
+        let _mock = mock::lock();
+        let messages = Arc::new(Mutex::new(Vec::new()));
+        set_logger({
+            let messages = Arc::clone(&messages);
+            move |level, message: &str| messages.lock().unwrap().push((level, message.to_owned()))
+        })
+        .unwrap();
+
+        // Longer than the buffer of the shim, so that it allocates.
+        let long = "x".repeat(1000);
+        for message in ["hooked 100%\n", &long] {
+            let message = CString::new(message).unwrap();
+            assert!(mock::log(ll::LOG_LEVEL_WARN, &message));
+        }
+        // Unknown levels are dropped.
+        mock::log(sys::log_level(9), &CString::new("unknown").unwrap());
+
+        assert_eq!(
+            *messages.lock().unwrap(),
+            [
+                (LogLevel::Warn, "hooked 100%".to_owned()),
+                (LogLevel::Warn, long),
+            ]
+        );
+    }
+}
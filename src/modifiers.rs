@@ -0,0 +1,310 @@
+use std::fmt;
+
+use bitflags::bitflags;
+
+use crate::{event::MouseButton, key::Key, sys};
+
+bitflags! {
+    /// The state of the modifier keys, mouse buttons and lock keys, as reported by the `mask` of
+    /// every event.
+    ///
+    /// The side-insensitive constants such as [`Modifiers::CTRL`] cover both the left and right
+    /// keys, so `contains(Modifiers::CTRL)` only holds while *both* are pressed; use
+    /// [`Modifiers::ctrl`] and friends to check for either side.
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+    pub struct Modifiers: u16 {
+        /// `MASK_SHIFT_L`.
+        const SHIFT_LEFT = sys::MASK_SHIFT_L;
+        /// `MASK_CTRL_L`.
+        const CTRL_LEFT = sys::MASK_CTRL_L;
+        /// `MASK_META_L`.
+        const META_LEFT = sys::MASK_META_L;
+        /// `MASK_ALT_L`.
+        const ALT_LEFT = sys::MASK_ALT_L;
+
+        /// `MASK_SHIFT_R`.
+        const SHIFT_RIGHT = sys::MASK_SHIFT_R;
+        /// `MASK_CTRL_R`.
+        const CTRL_RIGHT = sys::MASK_CTRL_R;
+        /// `MASK_META_R`.
+        const META_RIGHT = sys::MASK_META_R;
+        /// `MASK_ALT_R`.
+        const ALT_RIGHT = sys::MASK_ALT_R;
+
+        /// `MASK_SHIFT`.
+        const SHIFT = sys::MASK_SHIFT;
+        /// `MASK_CTRL`.
+        const CTRL = sys::MASK_CTRL;
+        /// `MASK_META`.
+        const META = sys::MASK_META;
+        /// `MASK_ALT`.
+        const ALT = sys::MASK_ALT;
+
+        /// `MASK_BUTTON1`.
+        const BUTTON1 = sys::MASK_BUTTON1;
+        /// `MASK_BUTTON2`.
+        const BUTTON2 = sys::MASK_BUTTON2;
+        /// `MASK_BUTTON3`.
+        const BUTTON3 = sys::MASK_BUTTON3;
+        /// `MASK_BUTTON4`.
+        const BUTTON4 = sys::MASK_BUTTON4;
+        /// `MASK_BUTTON5`.
+        const BUTTON5 = sys::MASK_BUTTON5;
+
+        /// `MASK_NUM_LOCK`.
+        const NUM_LOCK = sys::MASK_NUM_LOCK;
+        /// `MASK_CAPS_LOCK`.
+        const CAPS_LOCK = sys::MASK_CAPS_LOCK;
+        /// `MASK_SCROLL_LOCK`.
+        const SCROLL_LOCK = sys::MASK_SCROLL_LOCK;
+    }
+}
+
+impl Modifiers {
+    /// Every modifier key bit.
+    const KEYS: Self = Self::SHIFT
+        .union(Self::CTRL)
+        .union(Self::META)
+        .union(Self::ALT);
+
+    /// Every mouse button bit.
+    const BUTTONS: Self = Self::BUTTON1
+        .union(Self::BUTTON2)
+        .union(Self::BUTTON3)
+        .union(Self::BUTTON4)
+        .union(Self::BUTTON5);
+
+    /// Every lock key bit.
+    const LOCKS: Self = Self::NUM_LOCK
+        .union(Self::CAPS_LOCK)
+        .union(Self::SCROLL_LOCK);
+
+    /// Returns `true` if either shift key is held.
+    #[must_use]
+    pub const fn shift(self) -> bool {
+        self.intersects(Self::SHIFT)
+    }
+
+    /// Returns `true` if either control key is held.
+    #[must_use]
+    pub const fn ctrl(self) -> bool {
+        self.intersects(Self::CTRL)
+    }
+
+    /// Returns `true` if either meta (Windows or Command) key is held.
+    #[must_use]
+    pub const fn meta(self) -> bool {
+        self.intersects(Self::META)
+    }
+
+    /// Returns `true` if either alt (Option) key is held.
+    #[must_use]
+    pub const fn alt(self) -> bool {
+        self.intersects(Self::ALT)
+    }
+
+    /// Returns `true` if the left shift key is held.
+    #[must_use]
+    pub const fn shift_left(self) -> bool {
+        self.contains(Self::SHIFT_LEFT)
+    }
+
+    /// Returns `true` if the right shift key is held.
+    #[must_use]
+    pub const fn shift_right(self) -> bool {
+        self.contains(Self::SHIFT_RIGHT)
+    }
+
+    /// Returns `true` if the left control key is held.
+    #[must_use]
+    pub const fn ctrl_left(self) -> bool {
+        self.contains(Self::CTRL_LEFT)
+    }
+
+    /// Returns `true` if the right control key is held.
+    #[must_use]
+    pub const fn ctrl_right(self) -> bool {
+        self.contains(Self::CTRL_RIGHT)
+    }
+
+    /// Returns `true` if the left meta key is held.
+    #[must_use]
+    pub const fn meta_left(self) -> bool {
+        self.contains(Self::META_LEFT)
+    }
+
+    /// Returns `true` if the right meta key is held.
+    #[must_use]
+    pub const fn meta_right(self) -> bool {
+        self.contains(Self::META_RIGHT)
+    }
+
+    /// Returns `true` if the left alt key is held.
+    #[must_use]
+    pub const fn alt_left(self) -> bool {
+        self.contains(Self::ALT_LEFT)
+    }
+
+    /// Returns `true` if the right alt key is held.
+    #[must_use]
+    pub const fn alt_right(self) -> bool {
+        self.contains(Self::ALT_RIGHT)
+    }
+
+    /// Returns only the modifier key bits, without mouse buttons or lock keys.
+    #[must_use]
+    pub const fn keys(self) -> Self {
+        self.intersection(Self::KEYS)
+    }
+
+    /// Returns only the lock key bits.
+    #[must_use]
+    pub const fn locks(self) -> Self {
+        self.intersection(Self::LOCKS)
+    }
+
+    /// Returns the mouse buttons that are held down.
+    pub fn pressed_buttons(self) -> impl Iterator<Item = MouseButton> {
+        [
+            (Self::BUTTON1, MouseButton::Left),
+            (Self::BUTTON2, MouseButton::Right),
+            (Self::BUTTON3, MouseButton::Middle),
+            (Self::BUTTON4, MouseButton::Extra1),
+            (Self::BUTTON5, MouseButton::Extra2),
+        ]
+        .into_iter()
+        .filter_map(move |(mask, button)| self.contains(mask).then_some(button))
+    }
+
+    /// Returns the bit of a modifier key, or an empty mask for other keys.
+    pub(crate) fn of_key(key: Key) -> Self {
+        match key {
+            Key::ShiftLeft => Self::SHIFT_LEFT,
+            Key::ShiftRight => Self::SHIFT_RIGHT,
+            Key::ControlLeft => Self::CTRL_LEFT,
+            Key::ControlRight => Self::CTRL_RIGHT,
+            Key::AltLeft => Self::ALT_LEFT,
+            Key::AltRight => Self::ALT_RIGHT,
+            Key::MetaLeft => Self::META_LEFT,
+            Key::MetaRight => Self::META_RIGHT,
+            _ => Self::empty(),
+        }
+    }
+
+    /// Returns the bit of a mouse button, or an empty mask for buttons without one.
+    pub(crate) fn of_button(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => Self::BUTTON1,
+            MouseButton::Right => Self::BUTTON2,
+            MouseButton::Middle => Self::BUTTON3,
+            MouseButton::Extra1 => Self::BUTTON4,
+            MouseButton::Extra2 => Self::BUTTON5,
+            MouseButton::Other(_) => Self::empty(),
+        }
+    }
+
+    /// Returns `true` if any mouse button is held down.
+    #[must_use]
+    pub const fn any_button(self) -> bool {
+        self.intersects(Self::BUTTONS)
+    }
+}
+
+impl fmt::Display for Modifiers {
+    /// Writes the held modifier keys joined by `+`, such as `Ctrl+Shift`.
+    ///
+    /// Mouse buttons and lock keys are not included; an empty string is written when no modifier
+    /// key is held.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names = [
+            (self.ctrl(), "Ctrl"),
+            (self.alt(), "Alt"),
+            (self.shift(), "Shift"),
+            (self.meta(), "Meta"),
+        ];
+
+        let mut separator = "";
+        for (_, name) in names.into_iter().filter(|(held, _)| *held) {
+            f.write_str(separator)?;
+            f.write_str(name)?;
+            separator = "+";
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tells_sides_apart() {
+        let left = Modifiers::CTRL_LEFT | Modifiers::ALT_LEFT;
+        let right = Modifiers::SHIFT_RIGHT | Modifiers::META_RIGHT;
+
+        assert!(left.ctrl() && left.ctrl_left() && !left.ctrl_right());
+        assert!(left.alt() && left.alt_left() && !left.alt_right());
+        assert!(!left.shift() && !left.meta());
+
+        assert!(right.shift() && right.shift_right() && !right.shift_left());
+        assert!(right.meta() && right.meta_right() && !right.meta_left());
+        assert!(!right.ctrl() && !right.alt());
+
+        // The side-insensitive constants need both sides.
+        assert!(!left.contains(Modifiers::CTRL));
+        assert!(Modifiers::CTRL.ctrl_left() && Modifiers::CTRL.ctrl_right());
+    }
+
+    #[test]
+    fn separates_keys_locks_and_buttons() {
+        let mask = Modifiers::SHIFT_LEFT
+            | Modifiers::CAPS_LOCK
+            | Modifiers::NUM_LOCK
+            | Modifiers::BUTTON1
+            | Modifiers::BUTTON5;
+
+        assert_eq!(mask.keys(), Modifiers::SHIFT_LEFT);
+        assert_eq!(mask.locks(), Modifiers::CAPS_LOCK | Modifiers::NUM_LOCK);
+        assert!(mask.any_button());
+        assert_eq!(
+            mask.pressed_buttons().collect::<Vec<_>>(),
+            [MouseButton::Left, MouseButton::Extra2]
+        );
+
+        assert!(!mask.keys().any_button());
+        assert_eq!(Modifiers::empty().pressed_buttons().count(), 0);
+    }
+
+    #[test]
+    fn maps_keys_and_buttons_to_their_bit() {
+        assert_eq!(Modifiers::of_key(Key::ControlRight), Modifiers::CTRL_RIGHT);
+        assert_eq!(Modifiers::of_key(Key::MetaLeft), Modifiers::META_LEFT);
+        assert_eq!(Modifiers::of_key(Key::CapsLock), Modifiers::empty());
+        assert_eq!(Modifiers::of_key(Key::A), Modifiers::empty());
+
+        for button in Modifiers::all().pressed_buttons() {
+            let bit = Modifiers::of_button(button);
+            assert_eq!(bit.pressed_buttons().collect::<Vec<_>>(), [button]);
+        }
+        assert_eq!(
+            Modifiers::of_button(MouseButton::Other(6)),
+            Modifiers::empty()
+        );
+    }
+
+    #[test]
+    fn displays_held_keys() {
+        assert_eq!(Modifiers::empty().to_string(), "");
+        assert_eq!(Modifiers::SHIFT_RIGHT.to_string(), "Shift");
+        assert_eq!(
+            (Modifiers::META_LEFT | Modifiers::SHIFT | Modifiers::CTRL_RIGHT | Modifiers::ALT_LEFT)
+                .to_string(),
+            "Ctrl+Alt+Shift+Meta"
+        );
+        assert_eq!(
+            (Modifiers::CTRL_LEFT | Modifiers::BUTTON1 | Modifiers::SCROLL_LOCK).to_string(),
+            "Ctrl"
+        );
+    }
+}
@@ -0,0 +1,238 @@
+//! Synthesis of input events through `hook_post_event`.
+
+use std::ptr;
+
+use crate::{
+    builder::EventBuilder,
+    error::{Error, Result},
+    event::{Event, EventKind, MouseButton, WheelDirection},
+    key::Key,
+    modifiers::Modifiers,
+    sys,
+};
+
+/// Returns `true` if libuiohook can post events of `kind`.
+///
+/// Only physical state changes can be posted: [`EventKind::KeyTyped`] and
+/// [`EventKind::MouseClicked`] are derived by the system from presses and releases, and the hook
+/// events only describe the hook itself.
+#[must_use]
+pub fn can_post(kind: EventKind) -> bool {
+    matches!(
+        kind,
+        EventKind::KeyPressed
+            | EventKind::KeyReleased
+            | EventKind::MousePressed
+            | EventKind::MouseReleased
+            | EventKind::MouseMoved
+            | EventKind::MouseDragged
+            | EventKind::Wheel
+    )
+}
+
+/// Sends `event` to the system as if it came from an input device.
+///
+/// The hook does not need to be running. The `time` of the event is ignored by libuiohook.
+///
+/// libuiohook presses the modifier keys and buttons of the mask before the event and releases
+/// them after it, so the bit of the key or button the event itself presses or releases is cleared
+/// first; otherwise pressing it would end with it released.
+///
+/// # Errors
+///
+/// Returns [`Error::Unpostable`] for kinds that libuiohook cannot post; see [`can_post`]. With
+/// libuiohook 1.3, returns the error reported by `hook_post_event`. With the `dynamic` feature,
+/// returns `Error::Load` if libuiohook is not available.
+pub fn post(event: Event) -> Result<()> {
+    if !can_post(event.kind()) {
+        return Err(Error::Unpostable(event.kind()));
+    }
+
+    Error::ensure_loaded()?;
+
+    let own = match &event {
+        Event::KeyPressed(event) | Event::KeyReleased(event) => Modifiers::of_key(event.key),
+        Event::MousePressed(event) | Event::MouseReleased(event) => event
+            .button
+            .map_or_else(Modifiers::empty, Modifiers::of_button),
+        _ => Modifiers::empty(),
+    };
+
+    let mut raw = sys::uiohook_event::from(event);
+    raw.mask &= !own.bits();
+
+    // SAFETY: `raw` is a valid event whose data matches its type.
+    #[cfg(not(feature = "v1_3"))]
+    unsafe {
+        sys::hook_post_event(ptr::addr_of!(raw));
+        Ok(())
+    }
+
+    // SAFETY: as above.
+    #[cfg(feature = "v1_3")]
+    Error::check(unsafe { sys::hook_post_event(ptr::addr_of_mut!(raw)) })
+}
+
+/// Presses `key`.
+///
+/// # Errors
+///
+/// Returns the same errors as [`post`].
+pub fn press(key: Key) -> Result<()> {
+    post(EventBuilder::key_pressed(key).build())
+}
+
+/// Releases `key`.
+///
+/// # Errors
+///
+/// Returns the same errors as [`post`].
+pub fn release(key: Key) -> Result<()> {
+    post(EventBuilder::key_released(key).build())
+}
+
+/// Presses and releases `key`.
+///
+/// # Errors
+///
+/// Returns the same errors as [`post`]; the key is not released if pressing it failed.
+pub fn tap(key: Key) -> Result<()> {
+    press(key)?;
+    release(key)
+}
+
+/// Presses and releases `button` at the given position.
+///
+/// # Errors
+///
+/// Returns the same errors as [`post`]; the button is not released if pressing it failed.
+pub fn click(button: MouseButton, x: i16, y: i16) -> Result<()> {
+    post(EventBuilder::mouse_pressed(button).position(x, y).build())?;
+    post(EventBuilder::mouse_released(button).position(x, y).build())
+}
+
+/// Moves the cursor to the given position.
+///
+/// # Errors
+///
+/// Returns the same errors as [`post`].
+pub fn move_to(x: i16, y: i16) -> Result<()> {
+    post(EventBuilder::mouse_moved(x, y).build())
+}
+
+/// Scrolls the wheel by `rotation` clicks along `direction`; negative values scroll up or left.
+///
+/// # Errors
+///
+/// Returns the same errors as [`post`].
+pub fn scroll(direction: WheelDirection, rotation: i16) -> Result<()> {
+    post(
+        EventBuilder::wheel(direction, rotation)
+            .clicks(rotation.unsigned_abs())
+            .build(),
+    )
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{event::MouseEvent, sys::mock};
+
+    fn posted() -> Vec<Event> {
+        mock::take_posted()
+            .iter()
+            .map(|raw| Event::try_from(raw).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn posts_events() {
+        let _mock = mock::lock();
+
+        tap(Key::A).unwrap();
+        click(MouseButton::Left, 10, 20).unwrap();
+
+        // Posted without the bit of the button itself.
+        let Event::MousePressed(pressed) = EventBuilder::mouse_pressed(MouseButton::Left)
+            .position(10, 20)
+            .build()
+        else {
+            unreachable!();
+        };
+
+        assert_eq!(
+            posted(),
+            [
+                EventBuilder::key_pressed(Key::A).build(),
+                EventBuilder::key_released(Key::A).build(),
+                Event::MousePressed(MouseEvent {
+                    mask: Modifiers::empty(),
+                    ..pressed
+                }),
+                EventBuilder::mouse_released(MouseButton::Left)
+                    .position(10, 20)
+                    .build(),
+            ]
+        );
+    }
+
+    #[test]
+    fn clears_the_bit_of_the_posted_key_or_button() {
+        let _mock = mock::lock();
+
+        press(Key::ShiftLeft).unwrap();
+        post(
+            EventBuilder::key_pressed(Key::K)
+                .mask(Modifiers::SHIFT_LEFT)
+                .build(),
+        )
+        .unwrap();
+        post(
+            EventBuilder::key_released(Key::ShiftLeft)
+                .mask(Modifiers::SHIFT_LEFT)
+                .build(),
+        )
+        .unwrap();
+        post(EventBuilder::mouse_pressed(MouseButton::Left).build()).unwrap();
+        post(
+            EventBuilder::mouse_pressed(MouseButton::Right)
+                .mask(Modifiers::BUTTON1)
+                .build(),
+        )
+        .unwrap();
+        post(EventBuilder::mouse_dragged(MouseButton::Left, 1, 2).build()).unwrap();
+
+        let masks: Vec<_> = mock::take_posted().iter().map(|raw| raw.mask).collect();
+        assert_eq!(
+            masks,
+            [
+                0,
+                Modifiers::SHIFT_LEFT.bits(),
+                0,
+                0,
+                Modifiers::BUTTON1.bits(),
+                Modifiers::BUTTON1.bits(),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unpostable_events() {
+        let _mock = mock::lock();
+
+        assert!(matches!(
+            post(EventBuilder::key_typed('a').build()),
+            Err(Error::Unpostable(EventKind::KeyTyped))
+        ));
+        assert!(posted().is_empty());
+    }
+
+    #[cfg(feature = "v1_3")]
+    #[test]
+    fn reports_post_errors() {
+        let _mock = mock::lock();
+        mock::set_post_result(sys::UIOHOOK_FAILURE);
+
+        assert!(matches!(press(Key::A), Err(Error::Failure)));
+    }
+}
@@ -0,0 +1,380 @@
+//! Monitor geometry reported by `hook_create_screen_info`.
+
+use std::{ffi::c_void, os::raw::c_uchar, ptr, slice};
+
+use crate::{
+    error::{Error, Result},
+    sys,
+};
+
+extern "C" {
+    /// libuiohook allocates the screen info with `malloc`.
+    fn free(ptr: *mut c_void);
+}
+
+/// Returns the geometry of every monitor, in the order reported by the platform.
+///
+/// The list is empty if the platform cannot report it.
+///
+/// # Errors
+///
+/// With the `dynamic` feature, returns `Error::Load` if libuiohook is not available.
+pub fn screens() -> Result<Vec<Screen>> {
+    Error::ensure_loaded()?;
+
+    let mut count: c_uchar = 0;
+
+    // SAFETY: `count` is a valid pointer for the call.
+    let data = unsafe { sys::hook_create_screen_info(ptr::addr_of_mut!(count)) };
+    if data.is_null() {
+        return Ok(Vec::new());
+    }
+
+    // SAFETY: libuiohook returns an array of `count` entries, which is freed right after copying.
+    let screens = unsafe { slice::from_raw_parts(data, count.into()) }
+        .iter()
+        .map(Screen::from)
+        .collect();
+
+    // SAFETY: `data` was allocated with `malloc` and is not used afterwards.
+    unsafe { free(data.cast_mut().cast()) };
+
+    Ok(screens)
+}
+
+/// An axis-aligned rectangle in desktop coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// Horizontal position of the left edge.
+    pub x: i32,
+    /// Vertical position of the top edge.
+    pub y: i32,
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    pub height: u32,
+}
+
+impl Rect {
+    /// Returns the horizontal position just past the right edge.
+    #[must_use]
+    pub fn right(&self) -> i64 {
+        i64::from(self.x) + i64::from(self.width)
+    }
+
+    /// Returns the vertical position just past the bottom edge.
+    #[must_use]
+    pub fn bottom(&self) -> i64 {
+        i64::from(self.y) + i64::from(self.height)
+    }
+
+    /// Returns `true` if the point lies within the rectangle.
+    #[must_use]
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.x && i64::from(x) < self.right() && y >= self.y && i64::from(y) < self.bottom()
+    }
+
+    /// Returns the area shared with `other`, or `None` if they do not overlap.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let (x, y) = (self.x.max(other.x), self.y.max(other.y));
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        (right > x.into() && bottom > y.into()).then(|| Self {
+            x,
+            y,
+            width: to_u32(right - i64::from(x)),
+            height: to_u32(bottom - i64::from(y)),
+        })
+    }
+
+    /// Returns the smallest rectangle containing both `self` and `other`.
+    #[must_use]
+    pub fn union(&self, other: &Self) -> Self {
+        let (x, y) = (self.x.min(other.x), self.y.min(other.y));
+
+        Self {
+            x,
+            y,
+            width: to_u32(self.right().max(other.right()) - i64::from(x)),
+            height: to_u32(self.bottom().max(other.bottom()) - i64::from(y)),
+        }
+    }
+
+    /// Returns the number of pixels covered by the rectangle.
+    #[must_use]
+    pub fn area(&self) -> u64 {
+        u64::from(self.width) * u64::from(self.height)
+    }
+}
+
+/// Converts a length computed from two `i32` edges and a `u32` extent, which always fits.
+fn to_u32(length: i64) -> u32 {
+    u32::try_from(length).unwrap_or(u32::MAX)
+}
+
+/// A monitor, as reported by [`screens`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Screen {
+    /// Platform specific number of the monitor.
+    pub number: u8,
+    /// Position and size of the monitor on the desktop.
+    pub bounds: Rect,
+}
+
+impl Screen {
+    /// Returns `true` if the point lies on the monitor.
+    #[must_use]
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        self.bounds.contains(x, y)
+    }
+
+    /// Returns the area shared with `other`, or `None` if they do not overlap.
+    #[must_use]
+    pub fn intersection(&self, other: &Self) -> Option<Rect> {
+        self.bounds.intersection(&other.bounds)
+    }
+
+    /// Returns the number of pixels of the monitor.
+    #[must_use]
+    pub fn area(&self) -> u64 {
+        self.bounds.area()
+    }
+}
+
+impl From<&sys::screen_data> for Screen {
+    fn from(data: &sys::screen_data) -> Self {
+        Self {
+            number: data.number,
+            bounds: Rect {
+                x: data.x.into(),
+                y: data.y.into(),
+                width: data.width.into(),
+                height: data.height.into(),
+            },
+        }
+    }
+}
+
+/// The desktop spanned by every monitor.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct VirtualDesktop {
+    screens: Vec<Screen>,
+}
+
+impl VirtualDesktop {
+    /// Returns the desktop spanned by the monitors reported by [`screens`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`screens`].
+    pub fn query() -> Result<Self> {
+        screens().map(Self::new)
+    }
+
+    /// Returns the desktop spanned by `screens`.
+    #[must_use]
+    pub fn new(screens: Vec<Screen>) -> Self {
+        Self { screens }
+    }
+
+    /// Returns every monitor of the desktop.
+    #[must_use]
+    pub fn screens(&self) -> &[Screen] {
+        &self.screens
+    }
+
+    /// Returns the bounding box of every monitor, or `None` if there are none.
+    #[must_use]
+    pub fn bounds(&self) -> Option<Rect> {
+        self.screens
+            .iter()
+            .map(|screen| screen.bounds)
+            .reduce(|bounds, other| bounds.union(&other))
+    }
+
+    /// Returns the primary monitor, or `None` if there are none.
+    ///
+    /// Platforms place the primary monitor at the origin; if none is, the first monitor is
+    /// returned.
+    #[must_use]
+    pub fn primary(&self) -> Option<&Screen> {
+        self.screen_at(0, 0).or_else(|| self.screens.first())
+    }
+
+    /// Returns the monitor under the point, or `None` if it lies outside every monitor.
+    #[must_use]
+    pub fn screen_at(&self, x: i32, y: i32) -> Option<&Screen> {
+        self.screens.iter().find(|screen| screen.contains(x, y))
+    }
+}
+
+impl From<Vec<Screen>> for VirtualDesktop {
+    fn from(screens: Vec<Screen>) -> Self {
+        Self::new(screens)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(x: i32, y: i32, width: u32, height: u32) -> Rect {
+        Rect {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    fn screen(number: u8, bounds: Rect) -> Screen {
+        Screen { number, bounds }
+    }
+
+    #[test]
+    fn contains_points_up_to_the_edges() {
+        let r = rect(-10, 20, 30, 40);
+
+        assert!(r.contains(-10, 20));
+        assert!(r.contains(19, 59));
+        assert!(!r.contains(20, 30));
+        assert!(!r.contains(0, 60));
+        assert!(!r.contains(-11, 30));
+        assert!(!r.contains(0, 19));
+        assert!(!rect(0, 0, 0, 0).contains(0, 0));
+
+        // The far edges do not overflow.
+        let max = rect(i32::MAX - 1, i32::MAX - 1, u32::MAX, u32::MAX);
+        assert!(max.contains(i32::MAX, i32::MAX));
+        assert_eq!(max.right(), i64::from(i32::MAX) - 1 + i64::from(u32::MAX));
+    }
+
+    #[test]
+    fn intersects_overlapping_rects_only() {
+        let r = rect(0, 0, 100, 50);
+
+        assert_eq!(
+            r.intersection(&rect(60, -10, 100, 30)),
+            Some(rect(60, 0, 40, 20))
+        );
+        assert_eq!(
+            r.intersection(&rect(10, 10, 5, 5)),
+            Some(rect(10, 10, 5, 5))
+        );
+        assert_eq!(r.intersection(&r), Some(r));
+
+        // Sharing an edge or a corner is not overlapping.
+        assert_eq!(r.intersection(&rect(100, 0, 10, 10)), None);
+        assert_eq!(r.intersection(&rect(0, 50, 10, 10)), None);
+        assert_eq!(r.intersection(&rect(100, 50, 10, 10)), None);
+        assert_eq!(r.intersection(&rect(-20, -20, 10, 10)), None);
+        assert_eq!(r.intersection(&rect(10, 10, 0, 10)), None);
+
+        let (a, b) = (screen(1, r), screen(2, rect(50, 25, 100, 100)));
+        assert_eq!(a.intersection(&b), Some(rect(50, 25, 50, 25)));
+    }
+
+    #[test]
+    fn measures_areas() {
+        assert_eq!(rect(-5, -5, 1920, 1080).area(), 2_073_600);
+        assert_eq!(rect(0, 0, 0, 1080).area(), 0);
+        assert_eq!(
+            rect(0, 0, u32::MAX, u32::MAX).area(),
+            u64::from(u32::MAX) * u64::from(u32::MAX)
+        );
+        assert_eq!(screen(1, rect(0, 0, 10, 20)).area(), 200);
+        assert_eq!(
+            rect(0, 0, 10, 10).union(&rect(20, -5, 5, 5)),
+            rect(0, -5, 25, 15)
+        );
+    }
+
+    #[test]
+    fn finds_screens() {
+        let left = screen(2, rect(-1280, 0, 1280, 1024));
+        let main = screen(1, rect(0, 0, 1920, 1080));
+        let desktop = VirtualDesktop::new(vec![left, main]);
+
+        assert_eq!(desktop.primary(), Some(&main));
+        assert_eq!(desktop.screen_at(-1, 1023), Some(&left));
+        assert_eq!(desktop.screen_at(1919, 1079), Some(&main));
+        assert_eq!(desktop.screen_at(-1, 1024), None);
+        assert_eq!(desktop.screen_at(1920, 0), None);
+    }
+
+    #[test]
+    fn falls_back_to_the_first_screen() {
+        let first = screen(3, rect(100, 0, 800, 600));
+        let desktop = VirtualDesktop::from(vec![first, screen(4, rect(900, 0, 800, 600))]);
+
+        assert_eq!(desktop.screen_at(0, 0), None);
+        assert_eq!(desktop.primary(), Some(&first));
+
+        let empty = VirtualDesktop::default();
+        assert_eq!(empty.primary(), None);
+        assert_eq!(empty.screen_at(0, 0), None);
+        assert_eq!(empty.bounds(), None);
+    }
+
+    #[cfg(feature = "mock")]
+    #[test]
+    fn copies_the_screens() {
+        use crate::sys::mock;
+
+        let _mock = mock::lock();
+        assert_eq!(screens().unwrap(), []);
+
+        mock::set_screens(&[
+            sys::screen_data {
+                number: 1,
+                x: 0,
+                y: 0,
+                width: 1920,
+                height: 1080,
+            },
+            sys::screen_data {
+                number: 2,
+                x: 1920,
+                y: -200,
+                width: 1280,
+                height: 1024,
+            },
+        ]);
+
+        let desktop = VirtualDesktop::query().unwrap();
+        assert_eq!(
+            desktop.screens(),
+            [
+                Screen {
+                    number: 1,
+                    bounds: Rect {
+                        x: 0,
+                        y: 0,
+                        width: 1920,
+                        height: 1080,
+                    },
+                },
+                Screen {
+                    number: 2,
+                    bounds: Rect {
+                        x: 1920,
+                        y: -200,
+                        width: 1280,
+                        height: 1024,
+                    },
+                },
+            ]
+        );
+        assert_eq!(
+            desktop.bounds(),
+            Some(Rect {
+                x: 0,
+                y: -200,
+                width: 3200,
+                height: 1280,
+            })
+        );
+    }
+}
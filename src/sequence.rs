@@ -0,0 +1,593 @@
+//! Multi-stroke key sequences, such as `Ctrl+K Ctrl+C`.
+
+use std::{collections::HashSet, error, fmt, mem, str::FromStr, time::Duration};
+
+use crate::{
+    event::{Event, KeyboardEvent},
+    hotkey::{Hotkey, ParseHotkeyError, SideMatching},
+    key::Key,
+    modifiers::Modifiers,
+};
+
+/// A non-empty series of hotkeys to press one after the other.
+///
+/// Parses from strokes separated by whitespace, each in the format of [`Hotkey`], such as
+/// `Ctrl+K Ctrl+C` or `Space F F`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Sequence {
+    strokes: Vec<Hotkey>,
+}
+
+impl Sequence {
+    /// Creates a sequence, or returns `None` if `strokes` is empty.
+    #[must_use]
+    pub fn new(strokes: Vec<Hotkey>) -> Option<Self> {
+        (!strokes.is_empty()).then_some(Self { strokes })
+    }
+
+    /// Returns the strokes of the sequence.
+    #[must_use]
+    pub fn strokes(&self) -> &[Hotkey] {
+        &self.strokes
+    }
+}
+
+impl fmt::Display for Sequence {
+    /// Writes the strokes separated by spaces, in a format that parses back to the sequence.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        for stroke in &self.strokes {
+            write!(f, "{separator}{stroke}")?;
+            separator = " ";
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Sequence {
+    type Err = ParseHotkeyError;
+
+    /// Parses the strokes of the sequence; an empty string fails with
+    /// [`ParseHotkeyError::MissingKey`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let strokes = s
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<_, _>>()?;
+
+        Self::new(strokes).ok_or(ParseHotkeyError::MissingKey)
+    }
+}
+
+/// The error returned when a sequence would make a registered one impossible to complete, or the
+/// other way around.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SequenceConflict {
+    /// The sequence that was not inserted.
+    pub sequence: Sequence,
+    /// The registered sequence it conflicts with.
+    pub existing: Sequence,
+}
+
+impl fmt::Display for SequenceConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sequence {} conflicts with the registered sequence {}",
+            self.sequence, self.existing
+        )
+    }
+}
+
+impl error::Error for SequenceConflict {}
+
+/// What a key press did to the sequence in progress, returned by [`SequenceMatcher::feed`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SequenceStep<'a, T> {
+    /// The press continued a sequence, which needs more strokes.
+    Pending,
+    /// The press completed the sequence registered with this value.
+    Matched(&'a T),
+    /// The press did not continue the sequence in progress, which was abandoned. Holds every
+    /// press of the abandoned sequence, including this one.
+    Aborted(Vec<KeyboardEvent>),
+    /// The press came after the timeout, which abandoned the sequence in progress, and was matched
+    /// from the start instead.
+    Expired {
+        /// Every press of the abandoned sequence, not including this one.
+        aborted: Vec<KeyboardEvent>,
+        /// The value of the sequence the press completed on its own, if any. Whether it started a
+        /// longer one instead is told by [`SequenceMatcher::pending`].
+        matched: Option<&'a T>,
+    },
+}
+
+struct Node<T> {
+    children: Vec<(Hotkey, Node<T>)>,
+    /// The sequence ending at this node and its value.
+    entry: Option<(Sequence, T)>,
+}
+
+impl<T> Node<T> {
+    fn new() -> Self {
+        Self {
+            children: Vec::new(),
+            entry: None,
+        }
+    }
+
+    /// Returns a registered sequence that is a prefix of `strokes`, that `strokes` is a prefix of,
+    /// or that could be taken for `strokes` up to some stroke, if any.
+    fn conflict(&self, strokes: &[Hotkey], sides: SideMatching) -> Option<&Sequence> {
+        if let Some((sequence, _)) = &self.entry {
+            return Some(sequence);
+        }
+
+        let Some((stroke, rest)) = strokes.split_first() else {
+            return self
+                .children
+                .iter()
+                .find_map(|(_, child)| child.conflict(&[], sides));
+        };
+
+        self.children
+            .iter()
+            .filter(|(hotkey, _)| hotkey.overlaps(sides, *stroke, sides))
+            .find_map(|(hotkey, child)| {
+                // Only one child can be followed, so overlapping strokes must be the same.
+                let rest = if hotkey == stroke { rest } else { &[] };
+                child.conflict(rest, sides)
+            })
+    }
+}
+
+/// Recognizes registered key sequences in a stream of keyboard events.
+///
+/// Each press of a non-modifier key is a stroke, matched against the next strokes of the
+/// registered sequences like a [`Hotkey`]. Presses repeated while a key is held are ignored.
+/// Timing is measured with the `time` of the events rather than the clock, so recorded events
+/// behave like live ones.
+///
+/// ```
+/// use std::time::Duration;
+/// use uiohook::{EventBuilder, Key, Modifiers, SequenceMatcher, SequenceStep};
+///
+/// let mut matcher = SequenceMatcher::new(Duration::from_secs(1));
+/// matcher.insert("Ctrl+K Ctrl+C".parse()?, "comment")?;
+///
+/// let ctrl_k = EventBuilder::key_pressed(Key::K).mask(Modifiers::CTRL_LEFT).at(0);
+/// let ctrl_c = EventBuilder::key_pressed(Key::C).mask(Modifiers::CTRL_LEFT).at(500);
+///
+/// assert_eq!(matcher.feed(&ctrl_k.build()), Some(SequenceStep::Pending));
+/// assert_eq!(matcher.feed(&ctrl_c.build()), Some(SequenceStep::Matched(&"comment")));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub struct SequenceMatcher<T> {
+    root: Node<T>,
+    timeout: Duration,
+    sides: SideMatching,
+    /// Index of the child taken at each level by the sequence in progress.
+    path: Vec<usize>,
+    /// Presses of the sequence in progress.
+    strokes: Vec<KeyboardEvent>,
+    pressed: HashSet<Key>,
+}
+
+impl<T> SequenceMatcher<T> {
+    /// Creates a matcher without sequences that abandons a sequence when the next stroke takes
+    /// longer than `timeout`.
+    #[must_use]
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            root: Node::new(),
+            timeout,
+            sides: SideMatching::default(),
+            path: Vec::new(),
+            strokes: Vec::new(),
+            pressed: HashSet::new(),
+        }
+    }
+
+    /// Sets how the left and right modifier keys of the strokes are matched.
+    ///
+    /// Defaults to [`SideMatching::Insensitive`].
+    #[must_use]
+    pub fn sides(mut self, sides: SideMatching) -> Self {
+        self.sides = sides;
+        self
+    }
+
+    /// Registers `sequence` with a value reported when it is matched.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`SequenceConflict`] if the same strokes could complete a registered sequence
+    /// before `sequence`, or the other way around, such as `Ctrl+K` and `Ctrl+K Ctrl+C`. With
+    /// [`SideMatching::Sensitive`], strokes that only differ by sides but can be taken for each
+    /// other, such as `Ctrl+K` and `CtrlLeft+K`, also conflict.
+    pub fn insert(&mut self, sequence: Sequence, value: T) -> Result<(), SequenceConflict> {
+        // Sides are irrelevant when insensitive, so strokes differing by them share a node.
+        let strokes: Vec<_> = match self.sides {
+            SideMatching::Insensitive => sequence.strokes.iter().map(|s| s.either_side()).collect(),
+            SideMatching::Sensitive => sequence.strokes.clone(),
+        };
+
+        if let Some(existing) = self.root.conflict(&strokes, self.sides) {
+            return Err(SequenceConflict {
+                sequence,
+                existing: existing.clone(),
+            });
+        }
+
+        let mut node = &mut self.root;
+        for stroke in strokes {
+            let index = node
+                .children
+                .iter()
+                .position(|(hotkey, _)| *hotkey == stroke);
+            let index = index.unwrap_or_else(|| {
+                node.children.push((stroke, Node::new()));
+                node.children.len() - 1
+            });
+
+            node = &mut node.children[index].1;
+        }
+
+        node.entry = Some((sequence, value));
+        Ok(())
+    }
+
+    /// Advances the sequence in progress with `event`.
+    ///
+    /// Returns `None` for events that are not strokes, and for strokes that neither continue nor
+    /// abandon a sequence. A stroke arriving after the timeout abandons the sequence in progress
+    /// and starts over, as [`SequenceStep::Expired`].
+    pub fn feed(&mut self, event: &Event) -> Option<SequenceStep<'_, T>> {
+        let event = match event {
+            Event::KeyPressed(event) => event,
+            Event::KeyReleased(event) => {
+                self.pressed.remove(&event.key);
+                return None;
+            }
+            Event::HookEnabled(_) | Event::HookDisabled(_) => {
+                self.pressed.clear();
+                self.reset();
+                return None;
+            }
+            _ => return None,
+        };
+
+        let repeated = !self.pressed.insert(event.key);
+        if repeated || !Modifiers::of_key(event.key).is_empty() {
+            return None;
+        }
+
+        let expired = self.expire(event.time);
+        let step = self.advance(event);
+
+        match expired {
+            None => step,
+            Some(aborted) => Some(SequenceStep::Expired {
+                aborted,
+                matched: match step {
+                    Some(SequenceStep::Matched(value)) => Some(value),
+                    _ => None,
+                },
+            }),
+        }
+    }
+
+    /// Matches the stroke `event` against the next strokes of the sequence in progress.
+    fn advance(&mut self, event: &KeyboardEvent) -> Option<SequenceStep<'_, T>> {
+        let node = self
+            .path
+            .iter()
+            .fold(&self.root, |node, &index| &node.children[index].1);
+
+        let Some(index) = node
+            .children
+            .iter()
+            .position(|(hotkey, _)| hotkey.matches(event.key, event.mask, self.sides))
+        else {
+            if self.strokes.is_empty() {
+                return None;
+            }
+
+            self.path.clear();
+            let mut strokes = mem::take(&mut self.strokes);
+            strokes.push(*event);
+            return Some(SequenceStep::Aborted(strokes));
+        };
+
+        let node = &node.children[index].1;
+        if let Some((_, value)) = &node.entry {
+            self.path.clear();
+            self.strokes.clear();
+            return Some(SequenceStep::Matched(value));
+        }
+
+        self.path.push(index);
+        self.strokes.push(*event);
+        Some(SequenceStep::Pending)
+    }
+
+    /// Abandons the sequence in progress if its last stroke is more than the timeout older than
+    /// `time`, returning its presses.
+    pub fn expire(&mut self, time: u64) -> Option<Vec<KeyboardEvent>> {
+        let last = self.strokes.last()?;
+        if Duration::from_millis(time.saturating_sub(last.time)) <= self.timeout {
+            return None;
+        }
+
+        self.path.clear();
+        Some(mem::take(&mut self.strokes))
+    }
+
+    /// Returns the presses of the sequence in progress, empty if there is none.
+    #[must_use]
+    pub fn pending(&self) -> &[KeyboardEvent] {
+        &self.strokes
+    }
+
+    /// Abandons the sequence in progress.
+    pub fn reset(&mut self) {
+        self.path.clear();
+        self.strokes.clear();
+    }
+}
+
+impl<T> fmt::Debug for SequenceMatcher<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SequenceMatcher")
+            .field("timeout", &self.timeout)
+            .field("sides", &self.sides)
+            .field("pending", &self.strokes)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::EventBuilder;
+
+    const TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn sequence(s: &str) -> Sequence {
+        s.parse().unwrap()
+    }
+
+    fn press(key: Key, mask: Modifiers, time: u64) -> Event {
+        EventBuilder::key_pressed(key).mask(mask).at(time).build()
+    }
+
+    fn release(key: Key, time: u64) -> Event {
+        EventBuilder::key_released(key).at(time).build()
+    }
+
+    /// Returns the keys of the presses of an aborted sequence.
+    fn aborted<T>(step: Option<SequenceStep<'_, T>>) -> Vec<Key> {
+        match step {
+            Some(SequenceStep::Aborted(strokes)) => strokes.iter().map(|s| s.key).collect(),
+            _ => panic!("the sequence was not aborted"),
+        }
+    }
+
+    fn matcher(sides: SideMatching, sequences: &[&str]) -> SequenceMatcher<usize> {
+        let mut matcher = SequenceMatcher::new(TIMEOUT).sides(sides);
+        for (value, s) in sequences.iter().enumerate() {
+            matcher.insert(sequence(s), value).unwrap();
+        }
+
+        matcher
+    }
+
+    #[test]
+    fn shares_common_prefixes() {
+        let insensitive = matcher(
+            SideMatching::Insensitive,
+            &[
+                "Ctrl+K Ctrl+C",
+                "CtrlLeft+K Ctrl+U",
+                "CtrlRight+K X",
+                "Ctrl+J",
+            ],
+        );
+
+        assert_eq!(insensitive.root.children.len(), 2);
+        let (hotkey, ctrl_k) = &insensitive.root.children[0];
+        assert_eq!(*hotkey, "Ctrl+K".parse().unwrap());
+        assert_eq!(ctrl_k.children.len(), 3);
+        assert!(ctrl_k.entry.is_none());
+
+        // Sides are told apart when sensitive, so the strokes differing by them do not share.
+        let sensitive = matcher(SideMatching::Sensitive, &["CtrlLeft+K C", "CtrlRight+K C"]);
+        assert_eq!(sensitive.root.children.len(), 2);
+    }
+
+    #[test]
+    fn rejects_prefixes_and_extensions() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K", "Ctrl+J Ctrl+C"]);
+
+        for (s, existing) in [
+            ("Ctrl+K Ctrl+C", "Ctrl+K"),
+            ("CtrlLeft+K", "Ctrl+K"),
+            ("Ctrl+J", "Ctrl+J Ctrl+C"),
+            ("Ctrl+J CtrlRight+C", "Ctrl+J Ctrl+C"),
+        ] {
+            assert_eq!(
+                matcher.insert(sequence(s), 0),
+                Err(SequenceConflict {
+                    sequence: sequence(s),
+                    existing: sequence(existing),
+                }),
+                "{s}"
+            );
+        }
+
+        matcher.insert(sequence("Ctrl+J Ctrl+U"), 0).unwrap();
+        matcher.insert(sequence("Ctrl+Shift+K"), 0).unwrap();
+    }
+
+    #[test]
+    fn rejects_overlapping_sides() {
+        let mut matcher = matcher(SideMatching::Sensitive, &["CtrlLeft+K C", "Ctrl+J C"]);
+
+        // A press of the left Ctrl could continue either sequence.
+        for (s, existing) in [("Ctrl+K U", "CtrlLeft+K C"), ("CtrlLeft+J U", "Ctrl+J C")] {
+            assert_eq!(
+                matcher.insert(sequence(s), 0),
+                Err(SequenceConflict {
+                    sequence: sequence(s),
+                    existing: sequence(existing),
+                }),
+                "{s}"
+            );
+        }
+
+        matcher.insert(sequence("CtrlRight+K U"), 0).unwrap();
+        matcher.insert(sequence("CtrlLeft+K U"), 0).unwrap();
+    }
+
+    #[test]
+    fn matches_sequences() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K Ctrl+C", "Ctrl+K U"]);
+
+        assert_eq!(
+            matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 0)),
+            Some(SequenceStep::Pending)
+        );
+        assert_eq!(matcher.pending().len(), 1);
+        assert_eq!(
+            matcher.feed(&press(Key::U, Modifiers::empty(), 100)),
+            Some(SequenceStep::Matched(&1))
+        );
+        assert!(matcher.pending().is_empty());
+
+        // Strokes that start no sequence are ignored.
+        assert_eq!(
+            matcher.feed(&press(Key::C, Modifiers::CTRL_LEFT, 200)),
+            None
+        );
+        assert_eq!(matcher.feed(&release(Key::C, 300)), None);
+    }
+
+    #[test]
+    fn aborts_on_other_strokes() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K Ctrl+C"]);
+
+        matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 0));
+        assert_eq!(
+            aborted(matcher.feed(&press(Key::C, Modifiers::empty(), 100))),
+            [Key::K, Key::C]
+        );
+        assert!(matcher.pending().is_empty());
+    }
+
+    #[test]
+    fn aborts_after_the_timeout() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K Ctrl+C", "F5"]);
+
+        matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 0));
+        assert_eq!(matcher.expire(1000), None);
+        matcher.feed(&release(Key::K, 100));
+
+        // Starts nothing.
+        let step = matcher.feed(&press(Key::C, Modifiers::CTRL_LEFT, 1001));
+        assert!(matches!(
+            step,
+            Some(SequenceStep::Expired { ref aborted, matched: None })
+                if aborted.len() == 1 && aborted[0].key == Key::K
+        ));
+        assert!(matcher.pending().is_empty());
+
+        // Completes a sequence on its own.
+        matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 2000));
+        let step = matcher.feed(&press(Key::F5, Modifiers::empty(), 3001));
+        assert!(matches!(
+            step,
+            Some(SequenceStep::Expired { ref aborted, matched: Some(&1) }) if aborted.len() == 1
+        ));
+
+        matcher.feed(&release(Key::K, 3100));
+        matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 4000));
+        let expired = matcher.expire(5001).unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].key, Key::K);
+        assert!(matcher.pending().is_empty());
+    }
+
+    #[test]
+    fn matches_sequences_started_after_the_timeout() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K Ctrl+C"]);
+
+        matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 0));
+        matcher.feed(&release(Key::K, 100));
+
+        let step = matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 5000));
+        assert!(matches!(
+            step,
+            Some(SequenceStep::Expired { ref aborted, matched: None })
+                if aborted.len() == 1 && aborted[0].time == 0
+        ));
+        assert_eq!(matcher.pending().len(), 1);
+        assert_eq!(
+            matcher.feed(&press(Key::C, Modifiers::CTRL_LEFT, 5100)),
+            Some(SequenceStep::Matched(&0))
+        );
+    }
+
+    #[test]
+    fn ignores_repeats_and_modifiers() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K Ctrl+K"]);
+
+        assert_eq!(
+            matcher.feed(&press(Key::ControlLeft, Modifiers::CTRL_LEFT, 0)),
+            None
+        );
+        assert_eq!(
+            matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 0)),
+            Some(SequenceStep::Pending)
+        );
+        // Repeated while held.
+        assert_eq!(
+            matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 100)),
+            None
+        );
+        assert_eq!(
+            matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 200)),
+            None
+        );
+
+        assert_eq!(matcher.feed(&release(Key::K, 300)), None);
+        assert_eq!(
+            matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 400)),
+            Some(SequenceStep::Matched(&0))
+        );
+    }
+
+    #[test]
+    fn resets_when_the_hook_restarts() {
+        let mut matcher = matcher(SideMatching::Insensitive, &["Ctrl+K Ctrl+C"]);
+
+        for restart in [EventBuilder::hook_disabled(), EventBuilder::hook_enabled()] {
+            assert_eq!(
+                matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 0)),
+                Some(SequenceStep::Pending)
+            );
+            assert_eq!(matcher.feed(&restart.build()), None);
+            assert!(matcher.pending().is_empty());
+        }
+
+        // The key held across the restart is pressed anew rather than repeated.
+        assert_eq!(
+            matcher.feed(&press(Key::K, Modifiers::CTRL_LEFT, 100)),
+            Some(SequenceStep::Pending)
+        );
+        assert_eq!(
+            matcher.feed(&press(Key::C, Modifiers::CTRL_LEFT, 200)),
+            Some(SequenceStep::Matched(&0))
+        );
+    }
+}
@@ -0,0 +1,277 @@
+//! Keyboard and pointer parameters reported by the `hook_get_*` functions.
+
+use std::{collections::VecDeque, fmt, os::raw::c_long, thread, time::Duration};
+
+use crate::{
+    error::{Error, Result},
+    sys,
+};
+
+/// A snapshot of the keyboard and pointer settings of the system.
+///
+/// Every field is `None` when the platform does not report it. The values are passed through as
+/// reported, so their scale differs between platforms unless they are a [`Duration`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SystemSettings {
+    /// Keyboard auto repeat rate.
+    pub auto_repeat_rate: Option<u32>,
+    /// Delay before a held key starts repeating.
+    pub auto_repeat_delay: Option<Duration>,
+    /// Mouse acceleration multiplier.
+    pub pointer_acceleration_multiplier: Option<u32>,
+    /// Mouse acceleration threshold.
+    pub pointer_acceleration_threshold: Option<u32>,
+    /// Mouse sensitivity.
+    pub pointer_sensitivity: Option<u32>,
+    /// Longest interval between the clicks of a double or triple click.
+    pub multi_click_time: Option<Duration>,
+}
+
+impl SystemSettings {
+    /// Queries the current settings.
+    ///
+    /// # Errors
+    ///
+    /// With the `dynamic` feature, returns `Error::Load` if libuiohook is not available.
+    pub fn query() -> Result<Self> {
+        Error::ensure_loaded()?;
+        Ok(Self::read())
+    }
+
+    /// Queries the current settings once libuiohook is known to be loaded.
+    fn read() -> Self {
+        // SAFETY: the getters have no preconditions.
+        unsafe {
+            Self {
+                auto_repeat_rate: value(sys::hook_get_auto_repeat_rate()),
+                auto_repeat_delay: millis(sys::hook_get_auto_repeat_delay()),
+                pointer_acceleration_multiplier: value(
+                    sys::hook_get_pointer_acceleration_multiplier(),
+                ),
+                pointer_acceleration_threshold: value(
+                    sys::hook_get_pointer_acceleration_threshold(),
+                ),
+                pointer_sensitivity: value(sys::hook_get_pointer_sensitivity()),
+                multi_click_time: millis(sys::hook_get_multi_click_time()),
+            }
+        }
+    }
+
+    /// Returns the settings that differ between `self` and `other`.
+    pub fn diff(&self, other: &Self) -> impl Iterator<Item = Setting> {
+        [
+            (
+                Setting::AutoRepeatRate,
+                self.auto_repeat_rate != other.auto_repeat_rate,
+            ),
+            (
+                Setting::AutoRepeatDelay,
+                self.auto_repeat_delay != other.auto_repeat_delay,
+            ),
+            (
+                Setting::PointerAccelerationMultiplier,
+                self.pointer_acceleration_multiplier != other.pointer_acceleration_multiplier,
+            ),
+            (
+                Setting::PointerAccelerationThreshold,
+                self.pointer_acceleration_threshold != other.pointer_acceleration_threshold,
+            ),
+            (
+                Setting::PointerSensitivity,
+                self.pointer_sensitivity != other.pointer_sensitivity,
+            ),
+            (
+                Setting::MultiClickTime,
+                self.multi_click_time != other.multi_click_time,
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(setting, changed)| changed.then_some(setting))
+    }
+
+    /// Polls the settings every `interval`, yielding each change.
+    ///
+    /// The iterator never ends and blocks between polls, so it is best driven from a dedicated
+    /// thread.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`SystemSettings::query`].
+    pub fn watch(interval: Duration) -> Result<Watch> {
+        Ok(Watch {
+            interval,
+            current: Self::query()?,
+            pending: VecDeque::new(),
+        })
+    }
+}
+
+/// Converts a raw setting, where -1 means unavailable.
+fn value(raw: c_long) -> Option<u32> {
+    u32::try_from(raw).ok()
+}
+
+/// Converts a raw setting in milliseconds, where -1 means unavailable.
+fn millis(raw: c_long) -> Option<Duration> {
+    value(raw).map(|millis| Duration::from_millis(millis.into()))
+}
+
+/// One of the fields of [`SystemSettings`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Setting {
+    /// [`SystemSettings::auto_repeat_rate`].
+    AutoRepeatRate,
+    /// [`SystemSettings::auto_repeat_delay`].
+    AutoRepeatDelay,
+    /// [`SystemSettings::pointer_acceleration_multiplier`].
+    PointerAccelerationMultiplier,
+    /// [`SystemSettings::pointer_acceleration_threshold`].
+    PointerAccelerationThreshold,
+    /// [`SystemSettings::pointer_sensitivity`].
+    PointerSensitivity,
+    /// [`SystemSettings::multi_click_time`].
+    MultiClickTime,
+}
+
+impl fmt::Display for Setting {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::AutoRepeatRate => "auto repeat rate",
+            Self::AutoRepeatDelay => "auto repeat delay",
+            Self::PointerAccelerationMultiplier => "pointer acceleration multiplier",
+            Self::PointerAccelerationThreshold => "pointer acceleration threshold",
+            Self::PointerSensitivity => "pointer sensitivity",
+            Self::MultiClickTime => "multi-click time",
+        })
+    }
+}
+
+/// A change reported by [`Watch`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SettingChange {
+    /// The setting that changed.
+    pub setting: Setting,
+    /// The settings before the change.
+    pub previous: SystemSettings,
+    /// The settings after the change.
+    pub current: SystemSettings,
+}
+
+/// Iterator over the changes of the system settings, returned by [`SystemSettings::watch`].
+#[derive(Debug)]
+pub struct Watch {
+    interval: Duration,
+    current: SystemSettings,
+    pending: VecDeque<SettingChange>,
+}
+
+impl Watch {
+    /// Returns the settings as of the last poll.
+    #[must_use]
+    pub fn current(&self) -> &SystemSettings {
+        &self.current
+    }
+}
+
+impl Iterator for Watch {
+    type Item = SettingChange;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pending.is_empty() {
+            thread::sleep(self.interval);
+
+            let (previous, current) = (self.current, SystemSettings::read());
+            self.current = current;
+            self.pending
+                .extend(previous.diff(&current).map(|setting| SettingChange {
+                    setting,
+                    previous,
+                    current,
+                }));
+        }
+
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::sys::mock;
+
+    #[test]
+    fn reads_the_settings() {
+        let _mock = mock::lock();
+        assert_eq!(SystemSettings::query().unwrap(), SystemSettings::default());
+
+        mock::set_auto_repeat_delay(500);
+        mock::set_pointer_sensitivity(10);
+
+        let settings = SystemSettings::query().unwrap();
+        assert_eq!(
+            settings,
+            SystemSettings {
+                auto_repeat_delay: Some(Duration::from_millis(500)),
+                pointer_sensitivity: Some(10),
+                ..SystemSettings::default()
+            }
+        );
+        assert_eq!(
+            settings
+                .diff(&SystemSettings::default())
+                .collect::<Vec<_>>(),
+            [Setting::AutoRepeatDelay, Setting::PointerSensitivity]
+        );
+    }
+
+    #[test]
+    fn watches_the_changes() {
+        let _mock = mock::lock();
+        mock::set_auto_repeat_rate(25);
+
+        let mut watch = SystemSettings::watch(Duration::ZERO).unwrap();
+        let initial = *watch.current();
+        assert_eq!(initial.auto_repeat_rate, Some(25));
+
+        mock::set_auto_repeat_rate(30);
+        mock::set_multi_click_time(400);
+
+        let changed = SystemSettings {
+            auto_repeat_rate: Some(30),
+            multi_click_time: Some(Duration::from_millis(400)),
+            ..initial
+        };
+        assert_eq!(
+            watch.next(),
+            Some(SettingChange {
+                setting: Setting::AutoRepeatRate,
+                previous: initial,
+                current: changed,
+            })
+        );
+        assert_eq!(watch.current(), &changed);
+
+        // Both changes came from the same poll.
+        mock::set_auto_repeat_rate(-1);
+        assert_eq!(
+            watch.next(),
+            Some(SettingChange {
+                setting: Setting::MultiClickTime,
+                previous: initial,
+                current: changed,
+            })
+        );
+
+        assert_eq!(
+            watch.next(),
+            Some(SettingChange {
+                setting: Setting::AutoRepeatRate,
+                previous: changed,
+                current: SystemSettings {
+                    auto_repeat_rate: None,
+                    ..changed
+                },
+            })
+        );
+    }
+}
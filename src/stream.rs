@@ -0,0 +1,265 @@
+//! Delivery of events through a [`Stream`], enabled by the `async` feature.
+
+use std::{
+    any::Any,
+    collections::VecDeque,
+    os::raw::c_int,
+    pin::Pin,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    task::{Context, Poll, Waker},
+};
+
+use futures_core::Stream;
+
+use crate::{
+    channel::DEFAULT_CHANNEL_CAPACITY,
+    error::{Error, Result},
+    event::Event,
+    hook::{Hook, HookBuilder, HookHandle},
+};
+
+impl Hook {
+    /// Runs a hook that yields its events through the returned stream.
+    ///
+    /// Up to [`DEFAULT_CHANNEL_CAPACITY`] events are buffered; use [`HookBuilder::stream`] to
+    /// configure the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`HookBuilder::spawn`].
+    pub fn stream() -> Result<EventStream> {
+        Self::builder().stream(DEFAULT_CHANNEL_CAPACITY)
+    }
+}
+
+impl HookBuilder {
+    /// Runs the hook on a new thread, yielding its events through the returned stream.
+    ///
+    /// At most `capacity` events are buffered, and at least one: a `capacity` of 0 is treated as 1,
+    /// since the hook thread never waits for the stream and could not hand over any event.
+    /// Events arriving while the buffer is full are dropped and counted by
+    /// [`EventStream::dropped`]. Replaces any dispatcher set on the builder.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`HookBuilder::spawn`].
+    pub fn stream(self, capacity: usize) -> Result<EventStream> {
+        let shared = Arc::new(Mutex::new(Shared {
+            events: VecDeque::new(),
+            capacity: capacity.max(1),
+            dropped: 0,
+            waker: None,
+            finished: false,
+            error: None,
+        }));
+
+        let (sender, finisher) = (Sender(Arc::clone(&shared)), Sender(Arc::clone(&shared)));
+        let handle = self
+            .on_event(move |event| sender.send(event))
+            .on_finish(move |status| finisher.finish(status))
+            .spawn()?;
+
+        Ok(EventStream { shared, handle })
+    }
+}
+
+/// State shared between the dispatcher and the stream.
+#[derive(Debug)]
+struct Shared {
+    events: VecDeque<Event>,
+    capacity: usize,
+    dropped: u64,
+    waker: Option<Waker>,
+    /// Set by the hook thread once `hook_run` has returned.
+    finished: bool,
+    /// The error returned by `hook_run`, until it is yielded.
+    error: Option<Error>,
+}
+
+/// The hook thread's end of the stream.
+struct Sender(Arc<Mutex<Shared>>);
+
+impl Sender {
+    fn lock(&self) -> MutexGuard<'_, Shared> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn send(&self, event: Event) {
+        self.update(|shared| {
+            if shared.events.len() < shared.capacity {
+                shared.events.push_back(event);
+            } else {
+                shared.dropped += 1;
+            }
+        });
+    }
+
+    /// Ends the stream once the buffered events are yielded, with the error of `status` if any.
+    fn finish(&self, status: c_int) {
+        self.update(|shared| {
+            shared.finished = true;
+            shared.error = Error::check(status).err();
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut Shared)) {
+        let waker = {
+            let mut shared = self.lock();
+            f(&mut shared);
+            shared.waker.take()
+        };
+
+        // Woken outside the lock, so the task does not contend with the hook thread.
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
+
+/// A stream of the events of a running hook, returned by [`Hook::stream`].
+///
+/// The stream ends once the hook has stopped and every buffered event was yielded. If `hook_run`
+/// failed, its error is the last item. Polling never blocks; dropping the stream stops the hook
+/// and waits for its thread to finish.
+#[derive(Debug)]
+#[must_use = "streams do nothing unless polled"]
+pub struct EventStream {
+    shared: Arc<Mutex<Shared>>,
+    handle: HookHandle,
+}
+
+impl EventStream {
+    /// Returns the number of events dropped so far because the buffer was full.
+    #[must_use]
+    pub fn dropped(&self) -> u64 {
+        self.shared
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .dropped
+    }
+
+    /// Returns the payload of the oldest dispatcher panic that was not taken yet; see
+    /// [`HookHandle::take_panic`].
+    #[must_use]
+    pub fn take_panic(&self) -> Option<Box<dyn Any + Send>> {
+        self.handle.take_panic()
+    }
+}
+
+impl Stream for EventStream {
+    type Item = Result<Event, Error>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut shared = self.shared.lock().unwrap_or_else(PoisonError::into_inner);
+
+        if let Some(event) = shared.events.pop_front() {
+            return Poll::Ready(Some(Ok(event)));
+        }
+
+        if !shared.finished {
+            match &shared.waker {
+                Some(waker) if waker.will_wake(cx.waker()) => {}
+                _ => shared.waker = Some(cx.waker().clone()),
+            }
+
+            return Poll::Pending;
+        }
+
+        // The thread is joined when the stream is dropped, which is immediate by now.
+        Poll::Ready(shared.error.take().map(Err))
+    }
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use std::{
+        sync::Arc,
+        task::Wake,
+        thread::{self, Thread},
+    };
+
+    use super::*;
+    use crate::{
+        builder::EventBuilder,
+        error::X11Error,
+        key::Key,
+        sys::{self, mock},
+    };
+
+    /// Wakes the test thread parked in [`next`].
+    struct Unpark(Thread);
+
+    impl Wake for Unpark {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Waits for the next item of `stream` on the current thread.
+    fn next(stream: &mut EventStream) -> Option<Result<Event>> {
+        let waker = Arc::new(Unpark(thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match Pin::new(&mut *stream).poll_next(&mut cx) {
+                Poll::Ready(item) => return item,
+                Poll::Pending => thread::park(),
+            }
+        }
+    }
+
+    #[test]
+    fn yields_events_until_stopped() {
+        let _mock = mock::lock();
+        mock::set_stop_when_idle(true);
+        mock::script([EventBuilder::key_pressed(Key::A).build_raw()]);
+
+        let mut stream = Hook::stream().unwrap();
+
+        for expected in [
+            EventBuilder::hook_enabled(),
+            EventBuilder::key_pressed(Key::A),
+            EventBuilder::hook_disabled(),
+        ] {
+            assert_eq!(next(&mut stream).unwrap().unwrap(), expected.build());
+        }
+        assert!(next(&mut stream).is_none());
+    }
+
+    #[test]
+    fn drops_events_when_full() {
+        let _mock = mock::lock();
+        mock::set_stop_when_idle(true);
+        mock::script(
+            [Key::A, Key::B, Key::C].map(|key| EventBuilder::key_pressed(key).build_raw()),
+        );
+
+        // A capacity of 0 still buffers one event.
+        let mut stream = Hook::builder().stream(0).unwrap();
+        // Waits for the last event, which is dispatched after the hook reports being disabled.
+        while !stream.handle.is_finished() {
+            thread::yield_now();
+        }
+
+        assert_eq!(
+            next(&mut stream).unwrap().unwrap(),
+            EventBuilder::hook_enabled().build()
+        );
+        assert!(next(&mut stream).is_none());
+        assert_eq!(stream.dropped(), 4);
+    }
+
+    #[test]
+    fn yields_run_errors() {
+        let _mock = mock::lock();
+        mock::fail_run(sys::UIOHOOK_ERROR_X_OPEN_DISPLAY);
+
+        let mut stream = Hook::stream().unwrap();
+
+        assert!(matches!(
+            next(&mut stream),
+            Some(Err(Error::X11(X11Error::OpenDisplay)))
+        ));
+        assert!(next(&mut stream).is_none());
+    }
+}
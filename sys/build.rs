@@ -0,0 +1,237 @@
+fn main() {
+    #[cfg(all(feature = "bundled", feature = "dynamic"))]
+    println!("cargo:warning=the `dynamic` feature loads libuiohook at runtime, ignoring `bundled`");
+
+    #[cfg(all(feature = "mock", any(feature = "bundled", feature = "dynamic")))]
+    println!(
+        "cargo:warning=the `mock` feature replaces libuiohook, ignoring `bundled` and `dynamic`"
+    );
+
+    #[cfg(all(feature = "bundled", not(any(feature = "dynamic", feature = "mock"))))]
+    bundled::build();
+
+    // The library is loaded at runtime or replaced by the mock, so there is nothing to link.
+    #[cfg(not(any(feature = "bundled", feature = "dynamic", feature = "mock")))]
+    system::probe();
+}
+
+/// The `major.minor` version of the libuiohook API the bindings are built for.
+///
+/// This is the build metadata of the crate version, such as `1.2` for `0.1.0+1.2.2`, unless the
+/// `v1_3` feature selects the 1.3 API.
+#[cfg(not(any(feature = "dynamic", feature = "mock")))]
+fn api_version() -> String {
+    if cfg!(feature = "v1_3") {
+        return "1.3".to_owned();
+    }
+
+    let (_, upstream) = env!("CARGO_PKG_VERSION")
+        .split_once('+')
+        .expect("the crate version carries the libuiohook version as build metadata");
+
+    upstream
+        .splitn(3, '.')
+        .take(2)
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Finds a system installation of libuiohook.
+///
+/// `LIBUIOHOOK_LIB_DIR` takes precedence over pkg-config, and `LIBUIOHOOK_STATIC` requests static
+/// linking in both cases.
+#[cfg(not(any(feature = "bundled", feature = "dynamic", feature = "mock")))]
+mod system {
+    use std::{env, fs, path::Path};
+
+    pub fn probe() {
+        println!("cargo:rerun-if-env-changed=LIBUIOHOOK_LIB_DIR");
+        println!("cargo:rerun-if-env-changed=LIBUIOHOOK_STATIC");
+
+        let statik = env::var_os("LIBUIOHOOK_STATIC").map_or(false, |value| value != "0");
+
+        if let Some(dir) = env::var_os("LIBUIOHOOK_LIB_DIR") {
+            return probe_dir(Path::new(&dir), statik);
+        }
+
+        let mut config = pkg_config::Config::new();
+        config.statik(statik).cargo_metadata(false);
+
+        match config.probe("uiohook") {
+            Ok(lib) => {
+                check_version(&lib.version, "pkg-config");
+                config.cargo_metadata(true).probe("uiohook").unwrap();
+            }
+            // Not every distribution ships the pkg-config file, so still give the linker a chance
+            // to find the library on its default search path.
+            Err(_) => {
+                println!(
+                    "cargo:warning=pkg-config could not find libuiohook, linking `uiohook` from \
+                     the default search path; set LIBUIOHOOK_LIB_DIR if it lives elsewhere"
+                );
+                println!("cargo:rustc-link-lib={}", link_kind(statik));
+            }
+        }
+    }
+
+    fn probe_dir(dir: &Path, statik: bool) {
+        if !dir.is_dir() {
+            panic!(
+                "LIBUIOHOOK_LIB_DIR is set to {}, which is not a directory",
+                dir.display()
+            );
+        }
+
+        let candidates: &[&str] = if statik {
+            &["libuiohook.a", "uiohook.lib"]
+        } else {
+            &[
+                "libuiohook.so",
+                "libuiohook.dylib",
+                "uiohook.lib",
+                "libuiohook.a",
+            ]
+        };
+
+        if !candidates.iter().any(|file| dir.join(file).is_file()) {
+            panic!(
+                "LIBUIOHOOK_LIB_DIR is set to {}, but it does not contain {}",
+                dir.display(),
+                candidates.join(" or "),
+            );
+        }
+
+        // Installations from source put the pkg-config file next to the library, which is the
+        // only place left to learn the version from.
+        let pc = dir.join("pkgconfig").join("uiohook.pc");
+        match fs::read_to_string(&pc) {
+            Ok(contents) => {
+                let version = contents
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Version:"))
+                    .map(str::trim)
+                    .unwrap_or_default();
+
+                check_version(version, &pc.display().to_string());
+            }
+            Err(_) => println!(
+                "cargo:warning=could not verify the libuiohook version in {}, make sure it is {}.x",
+                dir.display(),
+                super::api_version(),
+            ),
+        }
+
+        println!("cargo:rustc-link-search=native={}", dir.display());
+        println!("cargo:rustc-link-lib={}", link_kind(statik));
+    }
+
+    /// Fails the build unless `found` shares the major and minor version of the libuiohook
+    /// release these bindings were written for.
+    fn check_version(found: &str, source: &str) {
+        let expected = super::api_version();
+
+        let mut parts = found.split('.');
+        let compatible = matches!(
+            (parts.next(), parts.next()),
+            (Some(major), Some(minor)) if format!("{major}.{minor}") == expected
+        );
+
+        if !compatible {
+            panic!(
+                "{source} reports libuiohook {found:?}, but libuiohook-sys {} requires {expected}.x; \
+                 install a compatible release, point LIBUIOHOOK_LIB_DIR at one, or enable the \
+                 `bundled` feature",
+                env!("CARGO_PKG_VERSION"),
+            );
+        }
+    }
+
+    fn link_kind(statik: bool) -> &'static str {
+        if statik {
+            "static=uiohook"
+        } else {
+            "uiohook"
+        }
+    }
+}
+
+/// Compiles the vendored libuiohook sources.
+#[cfg(all(feature = "bundled", not(any(feature = "dynamic", feature = "mock"))))]
+mod bundled {
+    use std::{env, fs, path::PathBuf};
+
+    pub fn build() {
+        println!("cargo:rerun-if-env-changed=LIBUIOHOOK_SRC_DIR");
+
+        let root = env::var_os("LIBUIOHOOK_SRC_DIR").map_or_else(
+            || PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("libuiohook"),
+            PathBuf::from,
+        );
+
+        if !root.join("include").join("uiohook.h").is_file() {
+            panic!(
+                "the `bundled` feature needs the libuiohook {version} sources in {root}; clone \
+                 them with `git clone --branch {version} https://github.com/kwhat/libuiohook \
+                 {root}` or point LIBUIOHOOK_SRC_DIR at an existing checkout",
+                version = super::api_version(),
+                root = root.display(),
+            );
+        }
+
+        let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap();
+        let platform = match target_os.as_str() {
+            "windows" => "windows",
+            "macos" => "darwin",
+            _ => "x11",
+        };
+
+        let src = root.join("src");
+        let platform_src = src.join(platform);
+
+        let mut build = cc::Build::new();
+        build
+            .include(root.join("include"))
+            .include(&src)
+            .include(&platform_src)
+            .file(src.join("logger.c"))
+            .warnings(false);
+
+        // The set of files differs between releases, but every one of them belongs in the build.
+        for entry in fs::read_dir(&platform_src).unwrap() {
+            let path = entry.unwrap().path();
+            if path.extension().map_or(false, |ext| ext == "c") {
+                build.file(path);
+            }
+        }
+
+        match platform {
+            "x11" => {
+                build
+                    .define("USE_XTEST", None)
+                    .define("USE_XINERAMA", None)
+                    .define("USE_XKBFILE", None);
+
+                for lib in ["X11", "Xtst", "Xinerama", "xkbfile"] {
+                    println!("cargo:rustc-link-lib={lib}");
+                }
+            }
+            "windows" => {
+                for lib in ["user32", "advapi32"] {
+                    println!("cargo:rustc-link-lib={lib}");
+                }
+            }
+            _ => {
+                build.define("USE_IOKIT", None);
+
+                for framework in ["ApplicationServices", "Carbon", "CoreFoundation", "IOKit"] {
+                    println!("cargo:rustc-link-lib=framework={framework}");
+                }
+            }
+        }
+
+        build.compile("uiohook");
+
+        println!("cargo:rerun-if-changed={}", src.display());
+        println!("cargo:include={}", root.join("include").display());
+    }
+}
@@ -0,0 +1,114 @@
+//! Runtime loading of libuiohook, enabled by the `dynamic` feature.
+
+use std::{env, error, ffi::OsString, fmt, sync::OnceLock, vec::Vec};
+
+use crate::Library;
+
+/// The library the free functions call through, set by the first successful [`load`].
+static LIBRARY: OnceLock<Library> = OnceLock::new();
+
+/// The error returned when libuiohook cannot be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The shared library could not be opened.
+    Library(libloading::Error),
+    /// The shared library does not export one of the libuiohook functions.
+    Symbol {
+        /// Name of the missing function.
+        name: &'static str,
+        /// The error reported by the loader.
+        source: libloading::Error,
+    },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Library(_) => f.write_str("failed to open the libuiohook shared library"),
+            Self::Symbol { name, .. } => write!(f, "libuiohook does not export `{name}`"),
+        }
+    }
+}
+
+impl error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Library(source) | Self::Symbol { source, .. } => Some(source),
+        }
+    }
+}
+
+/// Loads libuiohook for the free functions of this crate, if it was not loaded yet.
+///
+/// The library is looked up at `LIBUIOHOOK_PATH` if set, and otherwise by its platform specific
+/// file name on the default search path. Failures are not cached, so loading can be retried.
+///
+/// On X11, libuiohook connects to the display as soon as it is loaded: its constructor calls
+/// `XInitThreads`, then opens the display named by `DISPLAY`. Set `DISPLAY` before the first load,
+/// whether through this function or the first call of a libuiohook function, as later changes are
+/// not seen. As `XInitThreads` must be the first Xlib call of the process, load libuiohook before
+/// anything else uses Xlib.
+///
+/// # Errors
+///
+/// Returns the error of the first candidate that failed to load, which is either `LIBUIOHOOK_PATH`
+/// or the platform specific file name.
+pub fn load() -> Result<&'static Library, LoadError> {
+    if let Some(library) = LIBRARY.get() {
+        return Ok(library);
+    }
+
+    let mut candidates = Vec::new();
+    candidates.extend(env::var_os("LIBUIOHOOK_PATH"));
+    candidates.push(libloading::library_filename("uiohook"));
+
+    // Distributions usually only install the unversioned name along with the development files.
+    if cfg!(all(unix, not(target_os = "macos"))) {
+        candidates.extend(["libuiohook.so.1", "libuiohook.so.0"].map(OsString::from));
+    }
+
+    let mut first_error = None;
+    for candidate in candidates {
+        // SAFETY: the candidates name libuiohook, whose only initialization routine is the X11
+        // constructor. It calls `XInitThreads` and opens the display, which leaves the hook unable
+        // to run if that fails; the caller loads before any other use of Xlib, as documented above.
+        match unsafe { load_from(candidate) } {
+            Ok(library) => return Ok(library),
+            Err(err) => {
+                first_error.get_or_insert(err);
+            }
+        }
+    }
+
+    Err(first_error.expect("at least one candidate is always tried"))
+}
+
+/// Loads libuiohook from `path` for the free functions of this crate, if it was not loaded yet.
+///
+/// # Errors
+///
+/// Returns an error if the library cannot be opened or misses one of the libuiohook functions.
+///
+/// # Safety
+///
+/// Loading a library runs its initialization routines, which must be sound to run. Those of
+/// libuiohook are described on [`load`].
+pub unsafe fn load_from<P: AsRef<std::ffi::OsStr>>(path: P) -> Result<&'static Library, LoadError> {
+    if let Some(library) = LIBRARY.get() {
+        return Ok(library);
+    }
+
+    let library = Library::open(path)?;
+
+    // Another thread may have won the race, in which case its library is kept and ours unloaded.
+    Ok(LIBRARY.get_or_init(|| library))
+}
+
+/// Returns the loaded library, loading it first if needed.
+#[cfg_attr(feature = "mock", allow(dead_code))]
+pub(crate) fn library() -> &'static Library {
+    match load() {
+        Ok(library) => library,
+        Err(err) => panic!("{err}: {}", error::Error::source(&err).unwrap()),
+    }
+}
@@ -3,6 +3,9 @@
 
 //! Raw bindings for [libuiohook](https://github.com/kwhat/libuiohook).
 
+#[cfg(any(feature = "dynamic", feature = "mock"))]
+extern crate std;
+
 // DOCS(Unavailable): Document all items.
 //
 // fn docs: https://github.com/kwhat/libuiohook/tree/1.2/man
@@ -12,11 +15,19 @@
 #[rustfmt::skip]
 mod inner {
 
+// The mock only needs the types used outside of the function signatures.
+#[cfg_attr(feature = "mock", allow(unused_imports))]
 use core::{
     ffi::{c_char, c_uchar, c_uint, c_int, c_long},
     fmt, hash
 };
 
+#[cfg(feature = "v1_3")]
+use core::ffi::c_void;
+
+#[cfg(feature = "dynamic")]
+use crate::dynamic::LoadError;
+
 /* Begin Error Codes */
 
 // General error flags.
@@ -46,37 +57,96 @@ pub const UIOHOOK_ERROR_GET_RUNLOOP                 : c_int = 0x43;
 pub const UIOHOOK_ERROR_CREATE_OBSERVER             : c_int = 0x44;
 
 /* End Error Codes */
+
+/// Declares a C enum as a transparent integer, so that values unknown to these bindings can be
+/// received from C without undefined behaviour.
+macro_rules! c_enum {
+    (
+        $(#[doc = $doc:literal])*
+        pub struct $name:ident {
+            $($variant:ident = $value:literal,)*
+        }
+    ) => {
+        $(#[doc = $doc])*
+        ///
+        /// This is an integer rather than a Rust enum, as libuiohook may pass values these
+        /// bindings do not know about.
+        #[repr(transparent)]
+        #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name(pub c_uint);
+
+        impl $name {
+            $(
+                #[doc = concat!("`", stringify!($variant), "`.")]
+                pub const $variant: Self = Self($value);
+            )*
+
+            /// Returns the name of the constant, or `None` if the value is unknown.
+            #[must_use]
+            pub const fn name(self) -> Option<&'static str> {
+                match self {
+                    $(Self::$variant => Some(stringify!($variant)),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl fmt::Debug for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                match self.name() {
+                    Some(name) => f.write_str(name),
+                    None => f.debug_tuple(stringify!($name)).field(&self.0).finish(),
+                }
+            }
+        }
+    };
+}
+
 /* Begin Log Levels and Function Prototype */
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum log_level {
-    LOG_LEVEL_DEBUG = 1,
-    LOG_LEVEL_INFO,
-    LOG_LEVEL_WARN,
-    LOG_LEVEL_ERROR,
+c_enum! {
+    /// Severity of a message passed to [`logger_t`].
+    pub struct log_level {
+        LOG_LEVEL_DEBUG = 1,
+        LOG_LEVEL_INFO  = 2,
+        LOG_LEVEL_WARN  = 3,
+        LOG_LEVEL_ERROR = 4,
+    }
 }
 
 /// Logger callback function prototype.
+#[cfg(not(feature = "v1_3"))]
 pub type logger_t = extern "C" fn(c_uint, *const c_char, ...) -> bool;
 
+/// Logger callback function prototype.
+#[cfg(feature = "v1_3")]
+pub type logger_t = extern "C" fn(c_uint, *mut c_void, *const c_char, va_list);
+
+/// Opaque stand-in for the C `va_list` passed to [`logger_t`].
+///
+/// Every supported target passes a `va_list` argument as a pointer, but its contents can only be
+/// read from C.
+#[cfg(feature = "v1_3")]
+pub type va_list = *mut c_void;
+
 /* End Log Levels and Function Prototype */
 /* Begin Virtual Event Types and Data Structures */
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub enum event_type {
-    EVENT_HOOK_ENABLED = 1,
-    EVENT_HOOK_DISABLED,
-    EVENT_KEY_TYPED,
-    EVENT_KEY_PRESSED,
-    EVENT_KEY_RELEASED,
-    EVENT_MOUSE_CLICKED,
-    EVENT_MOUSE_PRESSED,
-    EVENT_MOUSE_RELEASED,
-    EVENT_MOUSE_MOVED,
-    EVENT_MOUSE_DRAGGED,
-    EVENT_MOUSE_WHEEL,
+c_enum! {
+    /// Kind of a [`uiohook_event`], selecting the variant of its `data`.
+    pub struct event_type {
+        EVENT_HOOK_ENABLED   = 1,
+        EVENT_HOOK_DISABLED  = 2,
+        EVENT_KEY_TYPED      = 3,
+        EVENT_KEY_PRESSED    = 4,
+        EVENT_KEY_RELEASED   = 5,
+        EVENT_MOUSE_CLICKED  = 6,
+        EVENT_MOUSE_PRESSED  = 7,
+        EVENT_MOUSE_RELEASED = 8,
+        EVENT_MOUSE_MOVED    = 9,
+        EVENT_MOUSE_DRAGGED  = 10,
+        EVENT_MOUSE_WHEEL    = 11,
+    }
 }
 
 // TODO(Unavailable): Implement `{Partial}Ord` in terms of `number`?
@@ -160,6 +230,8 @@ pub struct uiohook_event {
     pub r#type: event_type,
     pub time: u64,
     pub mask: u16,
+    /// Unused before libuiohook 1.3; since then, setting it from the dispatcher consumes the
+    /// event so it is not delivered to the rest of the system.
     pub reserved: u16,
     pub data: input_event_data,
 }
@@ -171,7 +243,8 @@ impl fmt::Debug for uiohook_event {
 
         let mut strt = f.debug_struct("uiohook_event");
 
-        strt.field("time", &self.time)
+        strt.field("type", &self.r#type)
+            .field("time", &self.time)
             .field("mask", &self.mask)
             .field("reserved", &self.reserved);
 
@@ -189,6 +262,9 @@ impl fmt::Debug for uiohook_event {
             | et::EVENT_MOUSE_DRAGGED => unsafe { &self.data.mouse }
 
             et::EVENT_MOUSE_WHEEL => unsafe { &self.data.wheel }
+
+            // The variant of `data` is unknown, so it cannot be read.
+            _ => return strt.finish_non_exhaustive(),
         };
 
         strt.field("data", data).finish()
@@ -228,12 +304,14 @@ impl PartialEq for uiohook_event {
             et::EVENT_MOUSE_WHEEL => {
                 unsafe { self.data.wheel == other.data.wheel }
             }
+
+            // The variant of `data` is unknown, so it cannot be read, nor claimed equal. This makes
+            // such events unequal to themselves, which is why the type does not implement `Eq`.
+            _ => false,
         }
     }
 }
 
-impl Eq for uiohook_event {}
-
 impl hash::Hash for uiohook_event {
     #[inline]
     fn hash<H: hash::Hasher>(&self, state: &mut H) {
@@ -258,12 +336,20 @@ impl hash::Hash for uiohook_event {
             | et::EVENT_MOUSE_DRAGGED => unsafe { self.data.mouse }.hash(state),
 
             et::EVENT_MOUSE_WHEEL => unsafe { self.data.wheel }.hash(state),
+
+            // The variant of `data` is unknown, so it cannot be read.
+            _ => {},
         };
     }
 }
 
+#[cfg(not(feature = "v1_3"))]
 pub type dispatcher_t = extern "C" fn(*const uiohook_event);
 
+/// The event is mutable so the dispatcher can set `reserved` to consume it.
+#[cfg(feature = "v1_3")]
+pub type dispatcher_t = extern "C" fn(*mut uiohook_event, *mut c_void);
+
 /* End Virtual Event Types and Data Structures */
 /* Begin Virtual Key Codes */
 
@@ -534,17 +620,115 @@ pub const WHEEL_HORIZONTAL_DIRECTION                :  u8 = 4;
 
 /* End Virtual Mouse Buttons */
 
-#[link(name = "uiohook")]
-extern "C" {
+/// Declares the libuiohook functions.
+///
+/// By default they are linked at build time. With the `dynamic` feature they are resolved at
+/// runtime into a [`Library`] instead, and the free functions call through the library loaded by
+/// [`load`]. With the `mock` feature the free functions are the fakes of [`mock`], whatever the
+/// other features.
+macro_rules! functions {
+    ($(
+        $(#[doc = $doc:literal])*
+        $(#[cfg($cfg:meta)])*
+        pub fn $name:ident($($arg:ident: $arg_ty:ty),*) $(-> $ret:ty)?;
+    )*) => {
+        #[cfg(not(any(feature = "dynamic", feature = "mock")))]
+        extern "C" {
+            $(
+                $(#[doc = $doc])*
+                $(#[cfg($cfg)])*
+                pub fn $name($($arg: $arg_ty),*) $(-> $ret)?;
+            )*
+        }
+
+        /// The libuiohook functions, resolved at runtime.
+        #[cfg(feature = "dynamic")]
+        pub struct Library {
+            $(
+                $(#[doc = $doc])*
+                $(#[cfg($cfg)])*
+                pub $name: unsafe extern "C" fn($($arg_ty),*) $(-> $ret)?,
+            )*
+            _library: libloading::Library,
+        }
+
+        #[cfg(feature = "dynamic")]
+        impl Library {
+            /// Opens the shared library at `path` and resolves every libuiohook function.
+            ///
+            /// # Safety
+            ///
+            /// Loading a library runs its initialization routines, which must be sound to run.
+            pub unsafe fn open<P: AsRef<std::ffi::OsStr>>(path: P) -> Result<Self, LoadError> {
+                let library = libloading::Library::new(path).map_err(LoadError::Library)?;
+
+                Ok(Self {
+                    $(
+                        $(#[cfg($cfg)])*
+                        $name: *library
+                            .get(concat!(stringify!($name), "\0").as_bytes())
+                            .map_err(|source| LoadError::Symbol {
+                                name: stringify!($name),
+                                source,
+                            })?,
+                    )*
+                    _library: library,
+                })
+            }
+        }
+
+        $(
+            #[cfg(all(feature = "dynamic", not(feature = "mock")))]
+            $(#[doc = $doc])*
+            $(#[cfg($cfg)])*
+            ///
+            /// # Safety
+            ///
+            /// Same as calling the function from the C library.
+            ///
+            /// # Panics
+            ///
+            /// Loads libuiohook on the first call, and panics if it cannot be loaded. As failures
+            /// are not cached, every call panics until loading succeeds; call
+            /// [`load`](crate::load) first to handle the error instead.
+            pub unsafe fn $name($($arg: $arg_ty),*) $(-> $ret)? {
+                (crate::dynamic::library().$name)($($arg),*)
+            }
+        )*
+
+        $(
+            #[cfg(feature = "mock")]
+            $(#[cfg($cfg)])*
+            pub use crate::mock::$name;
+        )*
+    };
+}
+
+functions! {
     /// Set the logger callback functions.
+    #[cfg(not(feature = "v1_3"))]
     pub fn hook_set_logger_proc(logger_proc: logger_t);
 
+    /// Set the logger callback functions.
+    #[cfg(feature = "v1_3")]
+    pub fn hook_set_logger_proc(logger_proc: logger_t, user_data: *mut c_void);
+
     /// Send a virtual event back to the system.
+    #[cfg(not(feature = "v1_3"))]
     pub fn hook_post_event(event: *const uiohook_event);
 
+    /// Send a virtual event back to the system.
+    #[cfg(feature = "v1_3")]
+    pub fn hook_post_event(event: *mut uiohook_event) -> c_int;
+
     /// Set the event callback function.
+    #[cfg(not(feature = "v1_3"))]
     pub fn hook_set_dispatch_proc(dispatch_proc: dispatcher_t);
 
+    /// Set the event callback function.
+    #[cfg(feature = "v1_3")]
+    pub fn hook_set_dispatch_proc(dispatch_proc: dispatcher_t, user_data: *mut c_void);
+
     /// Insert the event hook.
     pub fn hook_run() -> c_int;
 
@@ -575,4 +759,14 @@ extern "C" {
 
 }
 
+#[cfg(feature = "dynamic")]
+#[clippy::msrv = "1.70"]
+mod dynamic;
+
+#[cfg(feature = "mock")]
+#[clippy::msrv = "1.68"]
+pub mod mock;
+
+#[cfg(feature = "dynamic")]
+pub use dynamic::{load, load_from, LoadError};
 pub use inner::*;
@@ -0,0 +1,484 @@
+//! An in-process stand-in for libuiohook, enabled by the `mock` feature.
+//!
+//! The free functions of this crate are replaced by fakes that never touch the display server:
+//! `hook_run` dispatches the events queued with [`script`], `hook_post_event` records its events
+//! for [`posted`], [`log`] calls the registered logger, and the queries return the values
+//! configured here. Nothing is linked or loaded.
+//!
+//! The state is global to the process, so tests should hold the guard returned by [`lock`] while
+//! they use it.
+
+use core::{
+    ffi::{c_int, c_long, c_uchar, c_void},
+    mem, ptr,
+};
+use std::{
+    collections::VecDeque,
+    ffi::CStr,
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    vec::Vec,
+};
+
+use crate::{
+    dispatcher_t, event_type, input_event_data, log_level, logger_t, screen_data, uiohook_event,
+    UIOHOOK_FAILURE, UIOHOOK_SUCCESS,
+};
+
+static STATE: Mutex<State> = Mutex::new(State::new());
+
+/// Notified whenever `hook_run` may have something new to do.
+static CHANGED: Condvar = Condvar::new();
+
+/// Serializes the users of the mock, see [`lock`].
+static EXCLUSIVE: Mutex<()> = Mutex::new(());
+
+struct State {
+    events: VecDeque<uiohook_event>,
+    posted: Vec<uiohook_event>,
+    #[cfg(feature = "v1_3")]
+    consumed: Vec<uiohook_event>,
+    screens: Vec<screen_data>,
+    run_error: Option<c_int>,
+    #[cfg(feature = "v1_3")]
+    post_result: c_int,
+    stop_when_idle: bool,
+    running: bool,
+    stopping: bool,
+    /// The user data is kept as an address so that the state can be shared between threads.
+    #[cfg(feature = "v1_3")]
+    dispatcher: Option<(dispatcher_t, usize)>,
+    #[cfg(not(feature = "v1_3"))]
+    dispatcher: Option<dispatcher_t>,
+    #[cfg(feature = "v1_3")]
+    logger: Option<(logger_t, usize)>,
+    #[cfg(not(feature = "v1_3"))]
+    logger: Option<logger_t>,
+    auto_repeat_rate: c_long,
+    auto_repeat_delay: c_long,
+    pointer_acceleration_multiplier: c_long,
+    pointer_acceleration_threshold: c_long,
+    pointer_sensitivity: c_long,
+    multi_click_time: c_long,
+}
+
+impl State {
+    const fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+            posted: Vec::new(),
+            #[cfg(feature = "v1_3")]
+            consumed: Vec::new(),
+            screens: Vec::new(),
+            run_error: None,
+            #[cfg(feature = "v1_3")]
+            post_result: UIOHOOK_SUCCESS,
+            stop_when_idle: false,
+            running: false,
+            stopping: false,
+            dispatcher: None,
+            logger: None,
+            auto_repeat_rate: -1,
+            auto_repeat_delay: -1,
+            pointer_acceleration_multiplier: -1,
+            pointer_acceleration_threshold: -1,
+            pointer_sensitivity: -1,
+            multi_click_time: -1,
+        }
+    }
+}
+
+fn state() -> MutexGuard<'static, State> {
+    STATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn update(f: impl FnOnce(&mut State)) {
+    f(&mut state());
+    CHANGED.notify_all();
+}
+
+/// Exclusive access to the mock, returned by [`lock`]; resets it when dropped.
+#[must_use = "the mock is only reserved while the guard is held"]
+#[derive(Debug)]
+pub struct MockGuard {
+    _exclusive: MutexGuard<'static, ()>,
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        reset();
+    }
+}
+
+/// Waits until no other [`MockGuard`] is held, then resets the mock and returns a guard.
+///
+/// The test harness runs tests in parallel, while the mock, like libuiohook, is global.
+pub fn lock() -> MockGuard {
+    let exclusive = EXCLUSIVE.lock().unwrap_or_else(PoisonError::into_inner);
+    reset();
+
+    MockGuard {
+        _exclusive: exclusive,
+    }
+}
+
+/// Restores the initial configuration and clears the scripted and posted events.
+///
+/// A running hook keeps running, but its dispatcher is unregistered.
+pub fn reset() {
+    update(|state| {
+        let running = (state.running, state.stopping);
+        *state = State::new();
+        (state.running, state.stopping) = running;
+    });
+}
+
+/// Queues events for `hook_run` to dispatch, in order, after `EVENT_HOOK_ENABLED`.
+///
+/// Events are dispatched as given; nothing is filled in, not even the time.
+pub fn script<I: IntoIterator<Item = uiohook_event>>(events: I) {
+    update(|state| state.events.extend(events));
+}
+
+/// Makes `hook_run` return once every scripted event was dispatched, as if `hook_stop` was
+/// called, instead of waiting for more.
+///
+/// Defaults to `false`.
+pub fn set_stop_when_idle(stop_when_idle: bool) {
+    update(|state| state.stop_when_idle = stop_when_idle);
+}
+
+/// Makes `hook_run` fail with `code`, such as `UIOHOOK_ERROR_X_OPEN_DISPLAY`, without dispatching
+/// anything; `UIOHOOK_SUCCESS` runs the hook again.
+pub fn fail_run(code: c_int) {
+    update(|state| state.run_error = (code != UIOHOOK_SUCCESS).then_some(code));
+}
+
+/// Makes `hook_post_event` return `code`; the event is recorded either way.
+#[cfg(feature = "v1_3")]
+pub fn set_post_result(code: c_int) {
+    update(|state| state.post_result = code);
+}
+
+/// Returns `true` while `hook_run` is running.
+#[must_use]
+pub fn is_running() -> bool {
+    state().running
+}
+
+/// Returns the events passed to `hook_post_event` so far.
+#[must_use]
+pub fn posted() -> Vec<uiohook_event> {
+    state().posted.clone()
+}
+
+/// Returns the events passed to `hook_post_event` so far, and forgets them.
+#[must_use]
+pub fn take_posted() -> Vec<uiohook_event> {
+    mem::take(&mut state().posted)
+}
+
+/// Returns the dispatched events that the dispatcher consumed by setting `reserved`, and forgets
+/// them.
+#[cfg(feature = "v1_3")]
+#[must_use]
+pub fn take_consumed() -> Vec<uiohook_event> {
+    mem::take(&mut state().consumed)
+}
+
+/// Sets the monitors returned by `hook_create_screen_info`.
+///
+/// Defaults to none, for which a null pointer is returned like libuiohook does on failure.
+pub fn set_screens(screens: &[screen_data]) {
+    update(|state| state.screens = screens.to_vec());
+}
+
+macro_rules! settings {
+    ($($(#[doc = $doc:literal])* $set:ident => $name:ident, $field:ident;)*) => {
+        $(
+            $(#[doc = $doc])*
+            ///
+            /// Defaults to -1, which libuiohook returns when the setting is unavailable.
+            pub fn $set(value: c_long) {
+                update(|state| state.$field = value);
+            }
+
+            #[doc = concat!("Returns the value set with [`", stringify!($set), "`].")]
+            ///
+            /// # Safety
+            ///
+            /// Always safe; `unsafe` to match the real function.
+            pub unsafe extern "C" fn $name() -> c_long {
+                state().$field
+            }
+        )*
+    };
+}
+
+settings! {
+    /// Sets the value returned by `hook_get_auto_repeat_rate`.
+    set_auto_repeat_rate => hook_get_auto_repeat_rate, auto_repeat_rate;
+    /// Sets the value returned by `hook_get_auto_repeat_delay`.
+    set_auto_repeat_delay => hook_get_auto_repeat_delay, auto_repeat_delay;
+    /// Sets the value returned by `hook_get_pointer_acceleration_multiplier`.
+    set_pointer_acceleration_multiplier
+        => hook_get_pointer_acceleration_multiplier, pointer_acceleration_multiplier;
+    /// Sets the value returned by `hook_get_pointer_acceleration_threshold`.
+    set_pointer_acceleration_threshold
+        => hook_get_pointer_acceleration_threshold, pointer_acceleration_threshold;
+    /// Sets the value returned by `hook_get_pointer_sensitivity`.
+    set_pointer_sensitivity => hook_get_pointer_sensitivity, pointer_sensitivity;
+    /// Sets the value returned by `hook_get_multi_click_time`.
+    set_multi_click_time => hook_get_multi_click_time, multi_click_time;
+}
+
+/// Passes `message` to the registered logger at `level`, the way libuiohook reports its
+/// diagnostics.
+///
+/// Returns `false` if no logger is registered, or if the logger reports that it failed.
+pub fn log(level: log_level, message: &CStr) -> bool {
+    let logger = state().logger;
+
+    #[cfg(not(feature = "v1_3"))]
+    {
+        // The format consumes a single string argument.
+        logger.map_or(false, |logger| {
+            logger(level.0, b"%s\0".as_ptr().cast(), message.as_ptr())
+        })
+    }
+
+    #[cfg(feature = "v1_3")]
+    {
+        let Some((logger, user_data)) = logger else {
+            return false;
+        };
+
+        // Rust cannot create a `va_list`, so the message is the format itself, escaped so that it
+        // never reads an argument. The `va_list` is then only ever copied: a zeroed buffer as large
+        // as that of any supported target stands in for it.
+        let mut format = Vec::new();
+        for &byte in message.to_bytes_with_nul() {
+            format.push(byte);
+            if byte == b'%' {
+                format.push(byte);
+            }
+        }
+        let format = CStr::from_bytes_with_nul(&format).expect("a C string has no interior nul");
+        let mut args = [0_usize; 4];
+
+        logger(
+            level.0,
+            user_data as *mut c_void,
+            format.as_ptr(),
+            args.as_mut_ptr().cast(),
+        );
+        true
+    }
+}
+
+/// Sets the logger called by [`log`].
+///
+/// # Safety
+///
+/// Always safe; `unsafe` to match the real function.
+#[cfg(not(feature = "v1_3"))]
+pub unsafe extern "C" fn hook_set_logger_proc(logger_proc: logger_t) {
+    state().logger = Some(logger_proc);
+}
+
+/// Sets the logger called by [`log`], along with the user data passed to it.
+///
+/// # Safety
+///
+/// `user_data` must stay valid for `logger_proc` as long as it is registered.
+#[cfg(feature = "v1_3")]
+pub unsafe extern "C" fn hook_set_logger_proc(logger_proc: logger_t, user_data: *mut c_void) {
+    state().logger = Some((logger_proc, user_data as usize));
+}
+
+/// Records a copy of `event` for [`posted`].
+///
+/// # Safety
+///
+/// `event` must point to a valid event.
+#[cfg(not(feature = "v1_3"))]
+pub unsafe extern "C" fn hook_post_event(event: *const uiohook_event) {
+    state().posted.push((*event).clone());
+}
+
+/// Records a copy of `event` for [`posted`], and returns the code set with [`set_post_result`].
+///
+/// # Safety
+///
+/// `event` must point to a valid event.
+#[cfg(feature = "v1_3")]
+pub unsafe extern "C" fn hook_post_event(event: *mut uiohook_event) -> c_int {
+    let mut state = state();
+    state.posted.push((*event).clone());
+    state.post_result
+}
+
+/// Sets the dispatcher called by `hook_run`.
+///
+/// # Safety
+///
+/// Always safe; `unsafe` to match the real function.
+#[cfg(not(feature = "v1_3"))]
+pub unsafe extern "C" fn hook_set_dispatch_proc(dispatch_proc: dispatcher_t) {
+    state().dispatcher = Some(dispatch_proc);
+}
+
+/// Sets the dispatcher called by `hook_run`, along with the user data passed to it.
+///
+/// # Safety
+///
+/// `user_data` must stay valid for `dispatch_proc` as long as it is registered.
+#[cfg(feature = "v1_3")]
+pub unsafe extern "C" fn hook_set_dispatch_proc(
+    dispatch_proc: dispatcher_t,
+    user_data: *mut c_void,
+) {
+    state().dispatcher = Some((dispatch_proc, user_data as usize));
+}
+
+/// Dispatches `EVENT_HOOK_ENABLED`, the scripted events and `EVENT_HOOK_DISABLED`, waiting for more
+/// events until `hook_stop` is called.
+///
+/// Returns the code set with [`fail_run`] without dispatching anything, or `UIOHOOK_FAILURE` if
+/// the hook is already running.
+///
+/// # Safety
+///
+/// The registered dispatcher must be sound to call with the scripted events.
+pub unsafe extern "C" fn hook_run() -> c_int {
+    {
+        let mut state = state();
+
+        if let Some(code) = state.run_error {
+            return code;
+        }
+        if state.running {
+            return UIOHOOK_FAILURE;
+        }
+
+        state.running = true;
+        state.stopping = false;
+    }
+
+    dispatch(hook_event(event_type::EVENT_HOOK_ENABLED));
+
+    loop {
+        let mut state = state();
+        let event = loop {
+            if state.stopping {
+                break None;
+            }
+            if let Some(event) = state.events.pop_front() {
+                break Some(event);
+            }
+            if state.stop_when_idle {
+                break None;
+            }
+
+            state = CHANGED.wait(state).unwrap_or_else(PoisonError::into_inner);
+        };
+        drop(state);
+
+        match event {
+            Some(event) => dispatch(event),
+            None => break,
+        }
+    }
+
+    dispatch(hook_event(event_type::EVENT_HOOK_DISABLED));
+
+    update(|state| (state.running, state.stopping) = (false, false));
+    UIOHOOK_SUCCESS
+}
+
+/// Makes a running `hook_run` return; scripted events that were not dispatched yet are kept.
+///
+/// Returns `UIOHOOK_FAILURE` if the hook is not running.
+///
+/// # Safety
+///
+/// Always safe; `unsafe` to match the real function.
+pub unsafe extern "C" fn hook_stop() -> c_int {
+    let mut state = state();
+    if !state.running {
+        return UIOHOOK_FAILURE;
+    }
+
+    state.stopping = true;
+    drop(state);
+    CHANGED.notify_all();
+
+    UIOHOOK_SUCCESS
+}
+
+/// Returns a copy of the monitors set with [`set_screens`], allocated with `malloc` so that it can
+/// be released with `free`.
+///
+/// # Safety
+///
+/// `count` must be valid for writes.
+pub unsafe extern "C" fn hook_create_screen_info(count: *mut c_uchar) -> *const screen_data {
+    extern "C" {
+        fn malloc(size: usize) -> *mut c_void;
+    }
+
+    let state = state();
+    let screens = &state.screens[..state.screens.len().min(c_uchar::MAX.into())];
+
+    *count = 0;
+    if screens.is_empty() {
+        return ptr::null();
+    }
+
+    let data = malloc(mem::size_of_val(screens)).cast::<screen_data>();
+    if data.is_null() {
+        return ptr::null();
+    }
+
+    for (index, screen) in screens.iter().enumerate() {
+        data.add(index).write(screen.clone());
+    }
+
+    // Cannot truncate, as the slice was limited above.
+    #[allow(clippy::cast_possible_truncation)]
+    {
+        *count = screens.len() as c_uchar;
+    }
+    data
+}
+
+fn hook_event(r#type: event_type) -> uiohook_event {
+    uiohook_event {
+        r#type,
+        time: 0,
+        mask: 0,
+        reserved: 0,
+        // SAFETY: every variant of the union is plain integers, for which zero is valid.
+        data: unsafe { mem::zeroed::<input_event_data>() },
+    }
+}
+
+/// Passes `event` to the registered dispatcher, without holding the lock so that it can call back
+/// into the mock.
+#[cfg_attr(not(feature = "v1_3"), allow(unused_mut))]
+fn dispatch(mut event: uiohook_event) {
+    let dispatcher = state().dispatcher;
+
+    #[cfg(not(feature = "v1_3"))]
+    if let Some(dispatcher) = dispatcher {
+        dispatcher(ptr::addr_of!(event));
+    }
+
+    #[cfg(feature = "v1_3")]
+    if let Some((dispatcher, user_data)) = dispatcher {
+        dispatcher(ptr::addr_of_mut!(event), user_data as *mut c_void);
+
+        if event.reserved != 0 {
+            state().consumed.push(event);
+        }
+    }
+}
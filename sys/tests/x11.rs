@@ -0,0 +1,451 @@
+//! Runs the real X11 backend against a private Xvfb server.
+//!
+//! libuiohook opens its own connection to the display as soon as it is loaded, so `DISPLAY` has
+//! to point at the server before the process starts: `main` starts a single server, then runs the
+//! tests in a new process of this binary with `DISPLAY` set. The tests run one after the other,
+//! each running `hook_run`, posting events with `hook_post_event` and waiting for the dispatcher
+//! to see them. Nothing is checked when Xvfb is not installed, or when the `dynamic` feature
+//! cannot load libuiohook.
+
+use std::{
+    env,
+    io::ErrorKind,
+    panic,
+    path::Path,
+    process::{self, Child, Command, Stdio},
+    sync::{Condvar, Mutex, MutexGuard, PoisonError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use libuiohook_sys::*;
+
+/// How long to wait for the server to start and for each expected event.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Set in the environment of the process running the tests against the server.
+const RUNNER: &str = "LIBUIOHOOK_SYS_X11_RUNNER";
+
+const TESTS: &[(&str, fn())] = &[
+    ("keyboard", keyboard),
+    ("modifiers", modifiers),
+    ("buttons", buttons),
+    ("motion", motion),
+    ("wheel_rotation", wheel_rotation),
+];
+
+/// Every event seen by the dispatcher of the current test.
+static EVENTS: Mutex<Vec<uiohook_event>> = Mutex::new(Vec::new());
+static RECEIVED: Condvar = Condvar::new();
+
+fn main() {
+    if cfg!(not(target_os = "linux")) || cfg!(feature = "mock") {
+        println!("skipping: the X11 tests need the real libuiohook on Linux");
+        return;
+    }
+
+    if env::var_os(RUNNER).is_some() {
+        process::exit(run_tests());
+    }
+
+    let Some(xvfb) = Xvfb::start() else {
+        println!("skipping: Xvfb is not installed");
+        return;
+    };
+
+    let status = Command::new(env::current_exe().unwrap())
+        .args(env::args_os().skip(1))
+        .env("DISPLAY", format!(":{}", xvfb.display))
+        .env(RUNNER, "1")
+        .status()
+        .unwrap();
+
+    drop(xvfb);
+    process::exit(status.code().unwrap_or(1));
+}
+
+/// Runs the tests whose name contains one of the arguments, or every test without arguments, and
+/// returns the exit code.
+fn run_tests() -> i32 {
+    #[cfg(all(feature = "dynamic", not(feature = "mock")))]
+    if let Err(err) = load() {
+        println!("skipping: {err}");
+        return 0;
+    }
+
+    let filters: Vec<_> = env::args()
+        .skip(1)
+        .filter(|arg| !arg.starts_with('-'))
+        .collect();
+    let tests: Vec<_> = TESTS
+        .iter()
+        .filter(|(name, _)| filters.is_empty() || filters.iter().any(|f| name.contains(f.as_str())))
+        .collect();
+
+    let plural = if tests.len() == 1 { "" } else { "s" };
+    println!("\nrunning {} test{plural}", tests.len());
+
+    let mut failed = Vec::new();
+    for (name, test) in tests.iter().copied() {
+        let passed = panic::catch_unwind(test).is_ok();
+        println!("test {name} ... {}", if passed { "ok" } else { "FAILED" });
+
+        if !passed {
+            failed.push(*name);
+        }
+    }
+
+    let result = if failed.is_empty() { "ok" } else { "FAILED" };
+    println!(
+        "\ntest result: {result}. {} passed; {} failed\n",
+        tests.len() - failed.len(),
+        failed.len(),
+    );
+
+    i32::from(!failed.is_empty())
+}
+
+#[cfg(not(feature = "v1_3"))]
+extern "C" fn dispatch_proc(event: *const uiohook_event) {
+    // SAFETY: libuiohook passes a valid event that lives for the whole call.
+    record(unsafe { &*event });
+}
+
+#[cfg(feature = "v1_3")]
+extern "C" fn dispatch_proc(event: *mut uiohook_event, _user_data: *mut std::ffi::c_void) {
+    // SAFETY: libuiohook passes a valid event that lives for the whole call.
+    record(unsafe { &*event });
+}
+
+fn record(event: &uiohook_event) {
+    events().push(event.clone());
+    RECEIVED.notify_all();
+}
+
+fn events() -> MutexGuard<'static, Vec<uiohook_event>> {
+    EVENTS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// A private X server, terminated when dropped.
+struct Xvfb {
+    child: Child,
+    display: u32,
+}
+
+impl Xvfb {
+    /// Starts a server on the first free display, or returns `None` if Xvfb is not installed.
+    fn start() -> Option<Self> {
+        for display in 90..130 {
+            if Path::new(&format!("/tmp/.X{display}-lock")).exists() {
+                continue;
+            }
+
+            let child = Command::new("Xvfb")
+                .arg(format!(":{display}"))
+                .args(["-screen", "0", "1024x768x24", "-nolisten", "tcp"])
+                .args(["+extension", "RECORD", "+extension", "XTEST"])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .spawn();
+
+            let mut server = match child {
+                Ok(child) => Self { child, display },
+                Err(err) if err.kind() == ErrorKind::NotFound => return None,
+                Err(err) => panic!("failed to start Xvfb: {err}"),
+            };
+
+            if server.wait_ready() {
+                return Some(server);
+            }
+        }
+
+        panic!("no free display for Xvfb");
+    }
+
+    /// Waits for the server to listen on its display, returning `false` if it exited instead.
+    fn wait_ready(&mut self) -> bool {
+        let socket = format!("/tmp/.X11-unix/X{}", self.display);
+        let deadline = Instant::now() + TIMEOUT;
+
+        while Instant::now() < deadline {
+            if self.child.try_wait().unwrap().is_some() {
+                return false;
+            }
+            if Path::new(&socket).exists() {
+                return true;
+            }
+
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        panic!("Xvfb did not start on :{}", self.display);
+    }
+}
+
+impl Drop for Xvfb {
+    fn drop(&mut self) {
+        // Terminated rather than killed, so that the server removes its lock file and socket.
+        let _ = Command::new("kill")
+            .arg(self.child.id().to_string())
+            .status();
+
+        let deadline = Instant::now() + TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = self.child.try_wait() {
+                return;
+            }
+
+            thread::sleep(Duration::from_millis(20));
+        }
+
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// A hook running on its own thread against the server.
+struct Session {
+    hook: Option<JoinHandle<i32>>,
+}
+
+impl Session {
+    /// Runs a hook and waits for it to be enabled.
+    fn start() -> Self {
+        events().clear();
+
+        // SAFETY: the dispatcher only records events.
+        unsafe {
+            #[cfg(not(feature = "v1_3"))]
+            hook_set_dispatch_proc(dispatch_proc);
+            #[cfg(feature = "v1_3")]
+            hook_set_dispatch_proc(dispatch_proc, std::ptr::null_mut());
+        }
+
+        // SAFETY: the dispatcher is registered and the tests run one at a time.
+        let hook = thread::spawn(|| unsafe { hook_run() });
+
+        let mut session = Self { hook: Some(hook) };
+        session.expect("EVENT_HOOK_ENABLED", |event| {
+            event.r#type == event_type::EVENT_HOOK_ENABLED
+        });
+
+        session
+    }
+
+    /// Waits for an event matching `predicate` and returns it, consuming every event up to it.
+    fn expect(&mut self, what: &str, predicate: impl Fn(&uiohook_event) -> bool) -> uiohook_event {
+        let deadline = Instant::now() + TIMEOUT;
+        let mut events = events();
+
+        loop {
+            if let Some(index) = events.iter().position(&predicate) {
+                return events.drain(..=index).next_back().unwrap();
+            }
+
+            // A hook that failed to start would only be reported as a timeout.
+            if self.hook.as_ref().map_or(false, JoinHandle::is_finished) {
+                drop(events);
+                let status = self.hook.take().unwrap().join().unwrap();
+                panic!("hook_run returned {status} while waiting for {what}");
+            }
+
+            let now = Instant::now();
+            assert!(now < deadline, "timed out waiting for {what}: {events:#?}");
+
+            // Woken up regularly to notice the hook thread exiting.
+            events = RECEIVED
+                .wait_timeout(events, (deadline - now).min(Duration::from_millis(100)))
+                .unwrap_or_else(PoisonError::into_inner)
+                .0;
+        }
+    }
+
+    /// Stops the hook and checks that it exited cleanly.
+    fn stop(mut self) {
+        // SAFETY: stopping the hook is always sound.
+        assert_eq!(unsafe { hook_stop() }, UIOHOOK_SUCCESS);
+        self.expect("EVENT_HOOK_DISABLED", |event| {
+            event.r#type == event_type::EVENT_HOOK_DISABLED
+        });
+
+        let status = self.hook.take().unwrap().join().unwrap();
+        assert_eq!(status, UIOHOOK_SUCCESS);
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Only left running by a failed assertion; stop it before the next test starts one.
+        if let Some(hook) = self.hook.take() {
+            // SAFETY: stopping the hook is always sound.
+            unsafe { hook_stop() };
+            let _ = hook.join();
+        }
+    }
+}
+
+fn post(r#type: event_type, mask: u16, data: input_event_data) {
+    #[cfg_attr(not(feature = "v1_3"), allow(unused_mut))]
+    let mut event = uiohook_event {
+        r#type,
+        time: 0,
+        mask,
+        reserved: 0,
+        data,
+    };
+
+    // SAFETY: `event` is valid and its data matches its type.
+    #[cfg(not(feature = "v1_3"))]
+    unsafe {
+        hook_post_event(&event);
+    }
+
+    // SAFETY: as above.
+    #[cfg(feature = "v1_3")]
+    assert_eq!(unsafe { hook_post_event(&mut event) }, UIOHOOK_SUCCESS);
+}
+
+fn key(r#type: event_type, keycode: u16, mask: u16) {
+    let keyboard = keyboard_event_data {
+        keycode,
+        rawcode: 0,
+        keychar: CHAR_UNDEFINED,
+    };
+
+    post(r#type, mask, input_event_data { keyboard });
+}
+
+fn mouse(r#type: event_type, button: u16, x: i16, y: i16) {
+    let mouse = mouse_event_data {
+        button,
+        clicks: 1,
+        x,
+        y,
+    };
+
+    post(r#type, 0, input_event_data { mouse });
+}
+
+fn wheel(rotation: i16) {
+    let wheel = mouse_wheel_event_data {
+        clicks: 1,
+        x: 0,
+        y: 0,
+        r#type: WHEEL_UNIT_SCROLL,
+        amount: 3,
+        rotation,
+        direction: WHEEL_VERTICAL_DIRECTION,
+    };
+
+    post(event_type::EVENT_MOUSE_WHEEL, 0, input_event_data { wheel });
+}
+
+fn is_key(event: &uiohook_event, r#type: event_type, keycode: u16) -> bool {
+    // SAFETY: the data of keyboard events is `keyboard`.
+    event.r#type == r#type && unsafe { event.data.keyboard }.keycode == keycode
+}
+
+fn is_button(event: &uiohook_event, r#type: event_type, button: u16) -> bool {
+    // SAFETY: the data of mouse events is `mouse`.
+    event.r#type == r#type && unsafe { event.data.mouse }.button == button
+}
+
+fn keyboard() {
+    let mut session = Session::start();
+
+    key(event_type::EVENT_KEY_PRESSED, VC_A, 0);
+    let pressed = session.expect("A pressed", |event| {
+        is_key(event, event_type::EVENT_KEY_PRESSED, VC_A)
+    });
+    assert_eq!(pressed.mask & MASK_SHIFT, 0);
+
+    key(event_type::EVENT_KEY_RELEASED, VC_A, 0);
+    session.expect("A released", |event| {
+        is_key(event, event_type::EVENT_KEY_RELEASED, VC_A)
+    });
+
+    session.stop();
+}
+
+fn modifiers() {
+    let mut session = Session::start();
+
+    key(event_type::EVENT_KEY_PRESSED, VC_SHIFT_L, 0);
+    let shift = session.expect("Shift pressed", |event| {
+        is_key(event, event_type::EVENT_KEY_PRESSED, VC_SHIFT_L)
+    });
+    assert_ne!(shift.mask & MASK_SHIFT_L, 0, "{shift:?}");
+
+    key(event_type::EVENT_KEY_PRESSED, VC_A, MASK_SHIFT_L);
+    let a = session.expect("A pressed", |event| {
+        is_key(event, event_type::EVENT_KEY_PRESSED, VC_A)
+    });
+    assert_ne!(a.mask & MASK_SHIFT_L, 0, "{a:?}");
+
+    key(event_type::EVENT_KEY_RELEASED, VC_A, MASK_SHIFT_L);
+    key(event_type::EVENT_KEY_RELEASED, VC_SHIFT_L, 0);
+    let released = session.expect("Shift released", |event| {
+        is_key(event, event_type::EVENT_KEY_RELEASED, VC_SHIFT_L)
+    });
+    assert_eq!(released.mask & MASK_SHIFT, 0, "{released:?}");
+
+    session.stop();
+}
+
+fn buttons() {
+    let mut session = Session::start();
+
+    mouse(event_type::EVENT_MOUSE_PRESSED, MOUSE_BUTTON1, 120, 80);
+    let pressed = session.expect("button 1 pressed", |event| {
+        is_button(event, event_type::EVENT_MOUSE_PRESSED, MOUSE_BUTTON1)
+    });
+    // SAFETY: the data of mouse events is `mouse`.
+    let data = unsafe { pressed.data.mouse };
+    assert_eq!((data.x, data.y), (120, 80), "{pressed:?}");
+    assert_ne!(pressed.mask & MASK_BUTTON1, 0, "{pressed:?}");
+
+    mouse(event_type::EVENT_MOUSE_RELEASED, MOUSE_BUTTON1, 120, 80);
+    let released = session.expect("button 1 released", |event| {
+        is_button(event, event_type::EVENT_MOUSE_RELEASED, MOUSE_BUTTON1)
+    });
+    // SAFETY: as above.
+    let data = unsafe { released.data.mouse };
+    assert_eq!((data.x, data.y), (120, 80), "{released:?}");
+
+    session.stop();
+}
+
+fn motion() {
+    let mut session = Session::start();
+
+    for (x, y) in [(10, 20), (300, 400), (1023, 767)] {
+        mouse(event_type::EVENT_MOUSE_MOVED, MOUSE_NOBUTTON, x, y);
+
+        let moved = session.expect("motion", |event| {
+            // SAFETY: the data of mouse events is `mouse`.
+            event.r#type == event_type::EVENT_MOUSE_MOVED
+                && unsafe { (event.data.mouse.x, event.data.mouse.y) } == (x, y)
+        });
+        assert_eq!(moved.mask & (MASK_BUTTON1 | MASK_BUTTON2 | MASK_BUTTON3), 0);
+    }
+
+    session.stop();
+}
+
+fn wheel_rotation() {
+    let mut session = Session::start();
+
+    for rotation in [-1, 1] {
+        wheel(rotation);
+
+        let event = session.expect("wheel", |event| {
+            event.r#type == event_type::EVENT_MOUSE_WHEEL
+        });
+        // SAFETY: the data of wheel events is `wheel`.
+        let data = unsafe { event.data.wheel };
+        assert_eq!(data.direction, WHEEL_VERTICAL_DIRECTION, "{event:?}");
+        assert_eq!(data.rotation.signum(), rotation, "{event:?}");
+    }
+
+    session.stop();
+}